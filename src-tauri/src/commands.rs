@@ -1,156 +1,284 @@
 use crate::{
     config::ElasticsearchConfig,
-    elasticsearch::client::{
-        ESClient, IndexInfo, ClusterInfo, ShardInfo, ConnectionInfo,
-        SnapshotRepository, Snapshot, ClusterHealth
+    elasticsearch::{
+        client::{
+            ESClient, IndexInfo, ClusterInfo, ShardInfo, ConnectionInfo,
+            SnapshotRepository, Snapshot, ClusterHealth
+        },
+        health::ConnectionHealth,
+        version::ServerVersion,
     },
     error::{AppError, AppResult},
-    db::SavedConnection,
+    export::{ExportRequest, ExportTarget},
+    i18n::{self, Message},
+    inspector::{self, RequestFilter, RequestRecord},
+    logging::{self, LogEntry, LogLevel},
+    paging::{self, SearchPage},
+    db::{AppSettings, ConnectionSource, SavedConnection, VaultStatus},
+    profiles::{self, ProfileError, PROFILE_ID_PREFIX},
+    requests::CancelOutcome,
+    transfer::{self, ConflictStrategy, ExportFormat, ImportSummary, SecretHandling},
     AppState,
 };
 use serde_json::{Value, json};
 use serde::Deserialize;
 #[allow(unused_imports)]
 use serde as _;
-use tauri::State;
+use tauri::{AppHandle, State};
+use tracing::{debug, info, warn};
 use chrono::Utc;
-use url::Url;
+use std::path::Path;
+use std::time::Duration;
+
+/// Top-level body keys that only belong to write APIs (update, `_by_query`, reindex, index creation).
+const WRITE_BODY_KEYS: &[&str] = &[
+    "doc", "upsert", "doc_as_upsert", "script", "conflicts", "max_docs",
+    "source", "dest", "settings", "mappings", "aliases",
+];
+
+/// Refuses `action`, a message key, when the connection is flagged read-only.
+pub fn ensure_writable(client: &ESClient, action: &'static str) -> AppResult<()> {
+    if client.get_config().read_only {
+        return Err(AppError::ReadOnlyError(
+            Message::key("error.read_only_action")
+                .arg("action", Message::key(action))
+                .arg("connection", client.get_connection_info().name)
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a raw search against `index` with `body` would modify data.
+pub fn is_write_request(index: &str, body: &Value) -> bool {
+    let write_path = index
+        .split('/')
+        .any(|segment| matches!(segment, "_bulk" | "_update_by_query" | "_delete_by_query" | "_doc" | "_update"));
+    let write_body = body
+        .as_object()
+        .map(|obj| WRITE_BODY_KEYS.iter().any(|key| obj.contains_key(*key)))
+        .unwrap_or(false);
+    write_path || write_body
+}
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_info.id))]
 pub async fn connect_elasticsearch(
     config: ElasticsearchConfig,
     connection_info: ConnectionInfo,
+    app: AppHandle,
     state: State<'_, AppState>,
 ) -> AppResult<bool> {
-    println!("Received connection request with config: {:?}", config);
-    println!("Connection info: {:?}", connection_info);
+    debug!(config = ?config.redacted(), "Connecting");
 
-    let client = match ESClient::new(config, connection_info).await {
-        Ok(client) => {
-            println!("ES client created successfully");
-            client
-        },
+    let client = match ESClient::with_inspector(config, connection_info, state.inspector.clone()).await {
+        Ok(client) => client,
         Err(e) => {
-            println!("Failed to create ES client: {:?}", e);
+            warn!(error = %e, "Failed to create client");
             return Err(e);
         }
     };
 
     let is_connected = match client.test_connection().await {
-        Ok(result) => {
-            println!("Connection test result: {}", result);
-            result
-        },
+        Ok(result) => result,
         Err(e) => {
-            println!("Connection test failed: {:?}", e);
+            warn!(error = %e, "Connection test failed");
             return Err(e);
         }
     };
     
     if is_connected {
-        println!("Connection successful, storing client in state");
-        let mut es_clients = state.es_clients.lock().await;
-        es_clients.insert(client.get_connection_info().id.clone(), client);
+        info!("Connected");
+        let id = client.get_connection_info().id;
+        state.insert_client(id.clone(), client).await;
+        state.health.start(app, state.es_clients.clone(), id).await;
         Ok(true)
     } else {
-        println!("Connection test returned false");
-        Err(AppError::ConnectionError("无法连接到 Elasticsearch 服务器".to_string()))
+        warn!("Connection test returned false");
+        Err(AppError::ConnectionError(Message::key("connection.unreachable")))
     }
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn disconnect_elasticsearch(
     connection_id: String,
     state: State<'_, AppState>,
 ) -> AppResult<bool> {
-    let mut es_clients = state.es_clients.lock().await;
-    es_clients.remove(&connection_id);
+    state.health.stop(&connection_id).await;
+    state.searches.close_connection(&connection_id).await;
+    if let Some(client) = state.remove_client(&connection_id).await {
+        client.close().await;
+    }
     Ok(true)
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all)]
 pub async fn list_connections(state: State<'_, AppState>) -> AppResult<Vec<ConnectionInfo>> {
-    let es_clients = state.es_clients.lock().await;
-    let connections: Vec<ConnectionInfo> = es_clients
-        .values()
-        .map(|client| client.get_connection_info().clone())
+    let connections: Vec<ConnectionInfo> = state
+        .clients()
+        .await
+        .iter()
+        .map(|client| client.get_connection_info())
         .collect();
     Ok(connections)
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn get_connection_health(
+    connection_id: String,
+    state: State<'_, AppState>,
+) -> AppResult<Option<ConnectionHealth>> {
+    Ok(state.health.status(&connection_id).await)
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn get_server_version(
+    connection_id: String,
+    state: State<'_, AppState>,
+) -> AppResult<ServerVersion> {
+    match state.get_client(&connection_id).await {
+        Some(client) => client.server_version().cloned(),
+        None => Err(AppError::ConnectionError(Message::key("connection.not_connected"))),
+    }
+}
+
+/// Aborts a request started with `request_id` and stops its server-side tasks.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(request_id = %request_id))]
+pub async fn cancel_request(
+    request_id: String,
+    state: State<'_, AppState>,
+) -> AppResult<CancelOutcome> {
+    let connection_id = match state.requests.cancel(&request_id) {
+        Some(connection_id) => connection_id,
+        None => {
+            return Ok(CancelOutcome { cancelled: false, server_tasks_cancelled: 0 });
+        }
+    };
+
+    let server_tasks_cancelled = match state.get_client(&connection_id).await {
+        Some(client) => client.cancel_tasks(&request_id).await.unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to cancel server tasks");
+            0
+        }),
+        None => 0,
+    };
+    Ok(CancelOutcome { cancelled: true, server_tasks_cancelled })
+}
+
+/// Requests recorded for `connection_id`, newest first.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn list_requests(
+    connection_id: String,
+    filter: Option<RequestFilter>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<RequestRecord>> {
+    Ok(state.inspector.list(&connection_id, &filter.unwrap_or_default()))
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn clear_requests(connection_id: String, state: State<'_, AppState>) -> AppResult<()> {
+    state.inspector.clear(&connection_id).await
+}
+
+/// Recorded request `id` as a curl command, authenticating like the connection does.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn get_request_curl(
+    connection_id: String,
+    id: u64,
+    state: State<'_, AppState>,
+) -> AppResult<String> {
+    let record = state
+        .inspector
+        .get(&connection_id, id)
+        .ok_or_else(|| AppError::ValidationError(Message::key("request.not_found").arg("id", id)))?;
+    let client = state.get_client(&connection_id).await;
+    Ok(inspector::to_curl(&record, client.as_ref().map(|client| client.get_config())))
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn test_connection(connection_id: String, state: State<'_, AppState>) -> AppResult<bool> {
-    let es_clients = state.es_clients.lock().await;
-    match es_clients.get(&connection_id) {
+    match state.get_client(&connection_id).await {
         Some(client) => client.test_connection().await,
         None => Ok(false),
     }
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn get_current_config(connection_id: String, state: State<'_, AppState>) -> AppResult<Option<ElasticsearchConfig>> {
-    let es_clients = state.es_clients.lock().await;
-    Ok(es_clients.get(&connection_id).map(|client| client.get_config().clone()))
+    Ok(state.get_client(&connection_id).await.map(|client| client.get_config().clone()))
 }
 
 #[tauri::command(async)]
-pub async fn list_indices(connection_id: String, state: State<'_, AppState>) -> AppResult<Vec<IndexInfo>> {
-    let es_clients = state.es_clients.lock().await;
-    match es_clients.get(&connection_id) {
-        Some(client) => client.list_indices().await,
-        None => Err(AppError::ConnectionError("Not connected to Elasticsearch".to_string())),
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn list_indices(
+    connection_id: String,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<IndexInfo>> {
+    match state.get_client(&connection_id).await {
+        Some(client) => {
+            let request_id = request_id.as_deref();
+            state.requests
+                .run(request_id, &connection_id, client.list_indices(request_id))
+                .await
+        }
+        None => Err(AppError::ConnectionError(Message::key("connection.not_connected"))),
     }
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn get_cluster_info(connection_id: String, state: State<'_, AppState>) -> AppResult<ClusterInfo> {
-    println!("Attempting to get cluster info for connection_id: {}", connection_id);
-    let es_clients = state.es_clients.lock().await;
-    println!("ES clients lock acquired");
-    
-    match es_clients.get(&connection_id) {
-        Some(client) => {
-            println!("Found client for connection_id: {}", connection_id);
-            println!("Attempting to get cluster info...");
-            match client.get_cluster_info().await {
-                Ok(info) => {
-                    println!("Successfully retrieved cluster info: {:?}", info);
-                    Ok(info)
-                },
-                Err(e) => {
-                    println!("Failed to get cluster info: {:?}", e);
-                    Err(e)
-                }
+    match state.get_client(&connection_id).await {
+        Some(client) => match client.get_cluster_info().await {
+            Ok(info) => {
+                debug!(cluster = %info.name, status = %info.status, "Retrieved cluster info");
+                Ok(info)
+            },
+            Err(e) => {
+                warn!(error = %e, "Failed to get cluster info");
+                Err(e)
             }
         },
-        None => {
-            println!("No client found for connection_id: {}", connection_id);
-            Err(AppError::ConnectionError("Not connected to Elasticsearch".to_string()))
-        }
+        None => Err(AppError::ConnectionError(Message::key("connection.not_connected"))),
     }
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn get_shards_info(connection_id: String, state: State<'_, AppState>) -> AppResult<Vec<ShardInfo>> {
-    let es_clients = state.es_clients.lock().await;
-    match es_clients.get(&connection_id) {
+    match state.get_client(&connection_id).await {
         Some(client) => client.get_shards_info().await,
-        None => Err(AppError::ConnectionError("Not connected to Elasticsearch".to_string())),
+        None => Err(AppError::ConnectionError(Message::key("connection.not_connected"))),
     }
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn search(
     connection_id: String,
     index: String,
     query: String,
+    timeout_secs: Option<u64>,
+    request_id: Option<String>,
     state: State<'_, AppState>,
 ) -> AppResult<serde_json::Value> {
-    let es_clients = state.es_clients.lock().await;
-    match es_clients.get(&connection_id) {
+    match state.get_client(&connection_id).await {
         Some(client) => {
             let mut query_json: serde_json::Value = serde_json::from_str(&query)
-                .map_err(|e| AppError::ValidationError(format!("无效的查询JSON: {}", e)))?;
+                .map_err(|e| AppError::ValidationError(Message::key("search.invalid_query").arg("error", e)))?;
+            if is_write_request(&index, &query_json) {
+                ensure_writable(&client, "action.write_request")?;
+            }
 
             if let Some(obj) = query_json.as_object_mut() {
                 if !obj.contains_key("size") {
@@ -159,80 +287,291 @@ pub async fn search(
                 obj.insert("track_scores".to_string(), json!(true));
             }
 
-            let response = client
-                .get_client()
-                .search(elasticsearch::SearchParts::Index(&[&index]))
-                .body(query_json)
-                .send()
-                .await
-                .map_err(|e| AppError::ElasticsearchError(e.to_string()))?;
-
-            let response_body = response
-                .json::<serde_json::Value>()
-                .await
-                .map_err(|e| AppError::ElasticsearchError(e.to_string()))?;
-
-            Ok(response_body)
+            let request_id = request_id.as_deref();
+            let request = client.search(&index, query_json, timeout_secs.map(Duration::from_secs), request_id);
+            state.requests.run(request_id, &connection_id, request).await
         }
-        None => Err(AppError::ConnectionError("Not connected to Elasticsearch".to_string())),
+        None => Err(AppError::ConnectionError(Message::key("connection.not_connected"))),
     }
 }
 
+/// Opens a point in time on `index` and returns the first page of `query`. Further
+/// pages come from `search_page` with the cursor of the page before.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn open_search(
+    connection_id: String,
+    index: String,
+    query: String,
+    keep_alive_secs: Option<u64>,
+    timeout_secs: Option<u64>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<SearchPage> {
+    let query_json: Value = serde_json::from_str(&query)
+        .map_err(|e| AppError::ValidationError(Message::key("search.invalid_query").arg("error", e)))?;
+    let keep_alive = keep_alive_secs.map(Duration::from_secs).unwrap_or(paging::DEFAULT_KEEP_ALIVE);
+
+    let request_id = request_id.as_deref();
+    let request = state.searches.open(
+        &connection_id,
+        &index,
+        query_json,
+        keep_alive,
+        timeout_secs.map(Duration::from_secs),
+        request_id,
+    );
+    state.requests.run(request_id, &connection_id, request).await
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn search_page(
+    connection_id: String,
+    cursor: String,
+    timeout_secs: Option<u64>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<SearchPage> {
+    let request_id = request_id.as_deref();
+    let request = state.searches.page(&connection_id, &cursor, timeout_secs.map(Duration::from_secs), request_id);
+    state.requests.run(request_id, &connection_id, request).await
+}
+
+/// Ends a paged search and releases its point in time; returns whether it was still open.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(session_id = %session_id))]
+pub async fn close_search(session_id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    Ok(state.searches.close(&session_id).await)
+}
+
+/// Starts writing every hit of `query` on `index` to the target file and returns the
+/// export id.
+/// Progress arrives as `export-progress` events.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
+pub async fn start_export(
+    connection_id: String,
+    index: String,
+    query: String,
+    target: ExportTarget,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<String> {
+    let query: Value = serde_json::from_str(&query)
+        .map_err(|e| AppError::ValidationError(Message::key("search.invalid_query").arg("error", e)))?;
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
+
+    let request = ExportRequest {
+        index,
+        query,
+        format: target.format,
+        path: target.path,
+        columns: target.columns.filter(|columns| !columns.is_empty()),
+    };
+    state.exports.start(app, client, request)
+}
+
+/// Stops an export and removes its partial file; returns whether it was still running.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(export_id = %export_id))]
+pub async fn cancel_export(export_id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    Ok(state.exports.cancel(&export_id))
+}
+
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_info.id))]
 pub async fn save_connection_info(
     connection_info: ConnectionInfo,
     config: ElasticsearchConfig,
     state: State<'_, AppState>,
 ) -> AppResult<()> {
-    let host = config.hosts.first()
-        .ok_or_else(|| AppError::ValidationError("No host provided".to_string()))?;
-    let url = Url::parse(host)
-        .map_err(|e| AppError::ValidationError(format!("Invalid host URL: {}", e)))?;
+    config.validate()?;
+    if connection_info.id.starts_with(PROFILE_ID_PREFIX) {
+        return Err(AppError::ValidationError(
+            Message::key("profile.read_only_save")
+        ));
+    }
 
     let saved_conn = SavedConnection {
         id: connection_info.id.clone(),
         name: connection_info.name.clone(),
-        host: url.host_str()
-            .ok_or_else(|| AppError::ValidationError("Invalid host".to_string()))?
-            .to_string(),
-        port: url.port().unwrap_or(9200),
-        username: config.username.clone(),
-        password: config.password.clone(),
+        config,
         created_at: Utc::now().timestamp(),
         last_used_at: Utc::now().timestamp(),
+        source: ConnectionSource::Database,
     };
 
     state.db.save_connection(saved_conn).await
 }
 
 #[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %id))]
+pub async fn connect_saved_connection(
+    id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<ConnectionInfo> {
+    let saved = if id.starts_with(PROFILE_ID_PREFIX) {
+        profiles::get_profile(&id)?
+    } else {
+        state.db.get_connection(&id).await?
+    };
+    let source = saved.source;
+    let connection_info = ConnectionInfo {
+        id: saved.id.clone(),
+        name: saved.name.clone(),
+        hosts: saved.config.node_urls()?.iter().map(|url| url.to_string()).collect(),
+        is_active: false,
+    };
+
+    let client = ESClient::with_inspector(saved.config, connection_info, state.inspector.clone()).await?;
+    let connection_info = client.get_connection_info();
+    state.insert_client(saved.id.clone(), client).await;
+    state.health.start(app, state.es_clients.clone(), saved.id.clone()).await;
+    if source == ConnectionSource::Database {
+        state.db.update_last_used(saved.id).await?;
+    }
+
+    Ok(connection_info)
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
 pub async fn load_saved_connections(
     state: State<'_, AppState>,
 ) -> AppResult<Vec<SavedConnection>> {
-    state.db.list_connections().await
+    let mut connections = state.db.list_connections().await?;
+    // A broken profiles file or profile should not hide the other connections.
+    match profiles::load_profiles() {
+        Ok(profiles) => {
+            for profile in &profiles.errors {
+                warn!(profile = %profile.id, error = %profile.error, "Skipping connection profile");
+            }
+            connections.extend(profiles.connections);
+        }
+        Err(e) => warn!(error = %e, "Ignoring connection profiles"),
+    }
+    Ok(connections)
 }
 
+/// Profiles left out of `load_saved_connections` because they are invalid.
 #[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn list_profile_errors() -> AppResult<Vec<ProfileError>> {
+    Ok(profiles::load_profiles()?.errors)
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all, fields(connection_id = %id))]
 pub async fn delete_saved_connection(
     id: String,
     state: State<'_, AppState>,
 ) -> AppResult<()> {
+    if id.starts_with(PROFILE_ID_PREFIX) {
+        return Err(AppError::ValidationError(
+            Message::key("profile.read_only_delete")
+        ));
+    }
     state.db.delete_connection(id).await
 }
 
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn export_connections(
+    ids: Vec<String>,
+    path: String,
+    format: Option<ExportFormat>,
+    secrets: SecretHandling,
+    passphrase: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<usize> {
+    transfer::export_connections(&state.db, &ids, Path::new(&path), format, secrets, passphrase).await
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn import_connections(
+    path: String,
+    on_conflict: ConflictStrategy,
+    passphrase: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<ImportSummary> {
+    transfer::import_connections(&state.db, Path::new(&path), on_conflict, passphrase).await
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn get_settings(state: State<'_, AppState>) -> AppResult<AppSettings> {
+    state.db.load_settings().await
+}
+
+/// Saves `settings` and applies them right away; later messages use the new locale.
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn update_settings(
+    settings: AppSettings,
+    state: State<'_, AppState>,
+) -> AppResult<AppSettings> {
+    state.db.save_settings(&settings).await?;
+    i18n::set_locale(settings.locale);
+    state.inspector.set_persist(settings.persist_requests);
+    Ok(settings)
+}
+
+/// Buffered log entries at `level` (default `debug`) or more severe. Newer entries
+/// arrive as `log-entry` events.
+#[tauri::command(async)]
+pub async fn get_recent_logs(level: Option<LogLevel>) -> Vec<LogEntry> {
+    logging::recent(level.unwrap_or(LogLevel::Debug))
+}
+
+#[tauri::command(async)]
+pub async fn get_log_dir() -> Option<String> {
+    logging::log_dir().map(|dir| dir.display().to_string())
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn get_vault_status(state: State<'_, AppState>) -> AppResult<VaultStatus> {
+    state.db.vault_status().await
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn unlock_vault(
+    master_password: String,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.db.unlock(master_password).await
+}
+
+#[tauri::command(async)]
+#[tracing::instrument(skip_all)]
+pub async fn change_master_password(
+    current_password: String,
+    new_password: String,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.db.change_master_password(current_password, new_password).await
+}
+
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn list_snapshot_repositories(
     connection_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<SnapshotRepository>, String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<Vec<SnapshotRepository>> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
     client.list_snapshot_repositories()
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[derive(Debug, Deserialize)]
@@ -245,152 +584,138 @@ pub struct CreateRepositoryRequest {
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn create_snapshot_repository(
     connection_id: String,
     name: String,
     r#type: String,
     settings: Value,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    println!("=== Create Snapshot Repository Command Started ===");
-    println!("Command parameters received:");
-    println!("  connection_id: {:?}", connection_id);
-    println!("  name: {:?}", name);
-    println!("  type: {:?}", r#type);
-    println!("  settings: {:?}", settings);
-
-    let es_clients = state.es_clients.lock().await;
-    println!("ES clients lock acquired");
-    
-    println!("Looking for client with connection_id: {}", connection_id);
-    let client = match es_clients.get(&connection_id) {
-        Some(client) => {
-            println!("Found ES client for connection_id: {}", connection_id);
-            client
-        },
-        None => {
-            let error = format!("No client found for connection_id: {}", connection_id);
-            println!("Error: {}", error);
-            return Err(error);
-        }
-    };
+) -> AppResult<()> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
-    println!("Attempting to create snapshot repository");
-    println!("Parameters for create_snapshot_repository:");
-    println!("  Name: {}", name);
-    println!("  Type: {}", r#type);
-    println!("  Settings: {}", settings);
+    ensure_writable(&client, "action.create_repository")?;
 
+    // Repository settings can hold cloud credentials, so only the name and type are logged.
+    info!(repository = %name, kind = %r#type, "Creating snapshot repository");
     match client.create_snapshot_repository(&name, &r#type, settings).await {
-        Ok(_) => {
-            println!("Successfully created snapshot repository");
-            println!("=== Command completed successfully ===");
-            Ok(())
-        }
+        Ok(_) => Ok(()),
         Err(e) => {
-            let error = format!("Failed to create snapshot repository: {}", e);
-            println!("Error: {}", error);
-            println!("=== Command failed ===");
-            Err(error)
+            warn!(repository = %name, error = %e, "Failed to create snapshot repository");
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn list_snapshots(
     connection_id: String,
     repository: String,
     state: State<'_, AppState>,
-) -> Result<Vec<Snapshot>, String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<Vec<Snapshot>> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
     client.list_snapshots(&repository)
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn create_snapshot(
     connection_id: String,
     repository: String,
     snapshot: String,
     indices: Option<Vec<String>>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<()> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
+    ensure_writable(&client, "action.create_snapshot")?;
+
     client.create_snapshot(&repository, &snapshot, indices)
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn delete_snapshot(
     connection_id: String,
     repository: String,
     snapshot: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<()> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
+    ensure_writable(&client, "action.delete_snapshot")?;
+
     client.delete_snapshot(&repository, &snapshot)
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn restore_snapshot(
     connection_id: String,
     repository: String,
     snapshot: String,
     indices: Option<Vec<String>>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<()> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
+    ensure_writable(&client, "action.restore_snapshot")?;
+
     client.restore_snapshot(&repository, &snapshot, indices)
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn get_cluster_health(
     connection_id: String,
     state: State<'_, AppState>,
-) -> Result<ClusterHealth, String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<ClusterHealth> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
     client.get_cluster_health()
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, fields(connection_id = %connection_id))]
 pub async fn get_cluster_stats(
     connection_id: String,
+    timeout_secs: Option<u64>,
+    request_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients
-        .get(&connection_id)
-        .ok_or_else(|| "Not connected to Elasticsearch".to_string())?;
+) -> AppResult<Value> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
     
-    client.get_cluster_stats()
+    let request_id = request_id.as_deref();
+    let request = client.get_cluster_stats(timeout_secs.map(Duration::from_secs), request_id);
+    state.requests
+        .run(request_id, &connection_id, request)
         .await
-        .map_err(|e| e.to_string())
 } 
\ No newline at end of file
@@ -1,43 +1,568 @@
 use serde::{Deserialize, Serialize};
-use crate::error::{AppError, AppResult};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use elasticsearch::{
+    auth::Credentials,
+    http::{
+        headers::{HeaderMap, HeaderName, HeaderValue},
+        transport::CloudId,
+    },
+};
+use url::Url;
+use crate::{
+    error::{AppError, AppResult},
+    i18n::Message,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ElasticsearchConfig {
+    #[serde(default)]
     pub hosts: Vec<String>,
+    /// Elastic Cloud ID (`name:base64(host$es_uuid$kibana_uuid)`), used instead of `hosts`.
+    #[serde(default)]
+    pub cloud_id: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Either the base64 encoded API key or the raw `id:api_key` pair.
     pub api_key: Option<String>,
+    #[serde(default)]
+    pub bearer_token: Option<String>,
     pub ca_cert_path: Option<String>,
+    /// PKCS#12 archive (`.p12` / `.pfx`) holding the client certificate and its key.
+    #[serde(default)]
+    pub client_cert_path: Option<String>,
+    #[serde(default)]
+    pub client_cert_password: Option<String>,
+    #[serde(default)]
+    pub verification_mode: VerificationMode,
+    /// SHA-256 fingerprint of a CA certificate in the server chain, hex encoded (colons allowed).
+    #[serde(default)]
+    pub ca_fingerprint: Option<String>,
     pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub health_check: HealthCheckConfig,
+    #[serde(default)]
+    pub pool: PoolConfig,
+    #[serde(default)]
+    pub environment: Option<Environment>,
+    /// Refuse every mutating command on this connection.
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    /// Extra headers sent with every request, keyed by header name.
+    #[serde(default)]
+    pub headers: BTreeMap<String, CustomHeader>,
+    /// Reach `hosts` through an SSH bastion; each host is forwarded to a local port.
+    #[serde(default)]
+    pub ssh_tunnel: Option<SshTunnelConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxyConfig {
+    /// `http://` / `https://` for HTTP CONNECT proxies, `socks5://` / `socks5h://` for SOCKS5.
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Hosts reached directly: exact names, `.domain` / `*.domain` suffixes or `*`.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn is_socks(&self) -> bool {
+        self.url.starts_with("socks5://") || self.url.starts_with("socks5h://")
+    }
+
+    pub fn bypasses(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.no_proxy.iter().map(|entry| entry.trim().to_ascii_lowercase()).any(|entry| {
+            let suffix = entry.trim_start_matches('*');
+            entry == "*"
+                || entry == host
+                || (suffix.starts_with('.') && (host.ends_with(suffix) || host == suffix[1..]))
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomHeader {
+    pub value: Option<String>,
+    /// Store the value encrypted. `Authorization`, `Proxy-Authorization` and `Cookie` always are.
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// Headers the transport sets on every request, which would silently win over ours.
+const MANAGED_HEADERS: &[&str] = &["content-type", "accept", "user-agent", "host", "content-length"];
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+pub(crate) fn is_secret_header(name: &str, header: &CustomHeader) -> bool {
+    header.secret || SENSITIVE_HEADERS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SshTunnelConfig {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    pub user: String,
+    /// Private key in OpenSSH or PEM format; takes precedence over `password`.
+    #[serde(default)]
+    pub key_path: Option<String>,
+    #[serde(default)]
+    pub key_passphrase: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Trust a bastion missing from `~/.ssh/known_hosts`. Changed keys are always rejected.
+    #[serde(default)]
+    pub accept_unknown_host_key: bool,
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Production,
+    Staging,
+    Dev,
+    Custom(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub retry_on_status: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff_ms: 200,
+            max_backoff_ms: 5_000,
+            retry_on_status: vec![429, 502, 503, 504],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeSelection {
+    /// Spread requests over every node in turn.
+    #[default]
+    RoundRobin,
+    /// Stay on one node and only move to the next after a transport failure.
+    Sticky,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PoolConfig {
+    /// Discover the cluster's nodes through `_nodes/http` instead of using `hosts` only.
+    pub sniff: bool,
+    /// Re-sniff periodically. `None` only sniffs on connect and on failures.
+    pub sniff_interval_secs: Option<u64>,
+    pub sniff_on_failure: bool,
+    /// Skip master-eligible nodes that hold no data and run no ingest pipelines.
+    pub exclude_dedicated_masters: bool,
+    /// Only keep nodes with at least one of these roles. Empty keeps every node.
+    pub node_roles: Vec<String>,
+    pub selection: NodeSelection,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            sniff: false,
+            sniff_interval_secs: Some(300),
+            sniff_on_failure: true,
+            exclude_dedicated_masters: true,
+            node_roles: Vec::new(),
+            selection: NodeSelection::RoundRobin,
+        }
+    }
+}
+
+impl PoolConfig {
+    pub fn accepts(&self, roles: &[String]) -> bool {
+        let has = |role: &str| roles.iter().any(|r| r == role);
+        let dedicated_master = has("master")
+            && !roles.iter().any(|r| r.starts_with("data") || r == "ingest");
+        if self.exclude_dedicated_masters && dedicated_master {
+            return false;
+        }
+        self.node_roles.is_empty() || self.node_roles.iter().any(|role| has(role))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Consecutive failed pings after which the client is rebuilt. `0` disables reconnects.
+    pub reconnect_after_failures: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 30,
+            reconnect_after_failures: 3,
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff before retry number `attempt` (zero based), capped at `max_backoff_ms`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let millis = self.initial_backoff_ms.saturating_mul(1u64 << attempt.min(16));
+        Duration::from_millis(millis.min(self.max_backoff_ms))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationMode {
+    /// Verify the certificate chain and the hostname.
+    #[default]
+    Full,
+    /// Verify the certificate chain against `ca_cert_path` but skip hostname checks.
+    Certificate,
+    /// Accept any certificate. Only meant for local development clusters.
+    None,
 }
 
 impl Default for ElasticsearchConfig {
     fn default() -> Self {
         Self {
             hosts: vec!["http://localhost:9200".to_string()],
+            cloud_id: None,
             username: None,
             password: None,
             api_key: None,
+            bearer_token: None,
             ca_cert_path: None,
+            client_cert_path: None,
+            client_cert_password: None,
+            verification_mode: VerificationMode::Full,
+            ca_fingerprint: None,
             timeout_secs: Some(30),
+            retry: RetryPolicy::default(),
+            health_check: HealthCheckConfig::default(),
+            pool: PoolConfig::default(),
+            environment: None,
+            read_only: false,
+            proxy: None,
+            headers: BTreeMap::new(),
+            ssh_tunnel: None,
         }
     }
 }
 
 impl ElasticsearchConfig {
     pub fn validate(&self) -> AppResult<()> {
-        if self.hosts.is_empty() {
-            return Err(AppError::ValidationError("No Elasticsearch hosts provided".to_string()));
+        if let Some(cloud_id) = non_empty(&self.cloud_id) {
+            CloudId::parse(cloud_id.trim())
+                .map_err(|e| AppError::ValidationError(Message::key("config.invalid_cloud_id").arg("error", e)))?;
+            if self.credentials().is_none() && non_empty(&self.client_cert_path).is_none() {
+                return Err(AppError::ValidationError(
+                    Message::key("config.cloud_credentials_required")
+                ));
+            }
+        } else if self.hosts.is_empty() {
+            return Err(AppError::ValidationError(Message::key("config.no_hosts")));
         }
 
         for host in &self.hosts {
             if !host.starts_with("http://") && !host.starts_with("https://") {
                 return Err(AppError::ValidationError(
-                    format!("Invalid host URL: {}. Must start with http:// or https://", host)
+                    Message::key("config.invalid_host_scheme").arg("host", host)
+                ));
+            }
+        }
+
+        if self.timeout_secs == Some(0) {
+            return Err(AppError::ValidationError(Message::key("config.invalid_timeout")));
+        }
+        if self.health_check.enabled && self.health_check.interval_secs == 0 {
+            return Err(AppError::ValidationError(Message::key("config.invalid_health_interval")));
+        }
+
+        self.validate_auth()?;
+        self.validate_tls()?;
+        self.validate_proxy()?;
+        self.validate_ssh_tunnel()?;
+        self.validate_pool()?;
+        self.validate_headers()
+    }
+
+    fn validate_headers(&self) -> AppResult<()> {
+        for name in self.headers.keys() {
+            let lower = name.to_ascii_lowercase();
+            if MANAGED_HEADERS.contains(&lower.as_str()) {
+                return Err(AppError::ValidationError(Message::key("config.managed_header").arg("name", name)));
+            }
+            if lower == "authorization" && (self.credentials().is_some() || non_empty(&self.client_cert_path).is_some()) {
+                return Err(AppError::ValidationError(
+                    Message::key("config.authorization_conflict")
                 ));
             }
         }
+        self.header_map().map(|_| ())
+    }
+
+    /// Custom headers ready for the transport; secret values are marked sensitive.
+    pub fn header_map(&self) -> AppResult<HeaderMap> {
+        let mut map = HeaderMap::new();
+        for (name, header) in &self.headers {
+            let header_name = HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|_| AppError::ValidationError(Message::key("config.invalid_header_name").arg("name", name)))?;
+            let mut value = HeaderValue::from_str(header.value.as_deref().unwrap_or_default())
+                .map_err(|_| AppError::ValidationError(Message::key("config.invalid_header_value").arg("name", name)))?;
+            value.set_sensitive(is_secret_header(name, header));
+            map.insert(header_name, value);
+        }
+        Ok(map)
+    }
+
+    fn validate_pool(&self) -> AppResult<()> {
+        if !self.pool.sniff {
+            return Ok(());
+        }
+        if non_empty(&self.cloud_id).is_some() {
+            return Err(AppError::ValidationError(Message::key("config.sniff_cloud")));
+        }
+        if self.ssh_tunnel.is_some() {
+            return Err(AppError::ValidationError(
+                Message::key("config.sniff_ssh")
+            ));
+        }
+        if non_empty(&self.ca_fingerprint).is_some() {
+            return Err(AppError::ValidationError(Message::key("config.sniff_pinned")));
+        }
+        if self.pool.sniff_interval_secs == Some(0) {
+            return Err(AppError::ValidationError(Message::key("config.invalid_sniff_interval")));
+        }
+        Ok(())
+    }
+
+    fn validate_ssh_tunnel(&self) -> AppResult<()> {
+        let tunnel = match &self.ssh_tunnel {
+            Some(tunnel) => tunnel,
+            None => return Ok(()),
+        };
+
+        if non_empty(&self.cloud_id).is_some() {
+            return Err(AppError::ValidationError(Message::key("config.ssh_cloud")));
+        }
+        if self.proxy.is_some() {
+            return Err(AppError::ValidationError(Message::key("config.ssh_proxy")));
+        }
+        if tunnel.host.trim().is_empty() || tunnel.user.trim().is_empty() {
+            return Err(AppError::ValidationError(Message::key("config.ssh_incomplete")));
+        }
+        if tunnel.port == 0 {
+            return Err(AppError::ValidationError(Message::key("config.invalid_ssh_port")));
+        }
+        if non_empty(&tunnel.key_path).is_none() && non_empty(&tunnel.password).is_none() {
+            return Err(AppError::ValidationError(Message::key("config.ssh_credentials_required")));
+        }
+        // The transport reaches https nodes at 127.0.0.1, which no certificate is issued
+        // for; only the pinned relay keeps checking against the configured hostnames.
+        if self.verification_mode == VerificationMode::Full
+            && non_empty(&self.ca_fingerprint).is_none()
+            && self.hosts.iter().any(|host| host.starts_with("https://"))
+        {
+            return Err(AppError::ValidationError(Message::key("config.ssh_full_verification")));
+        }
+
+        Ok(())
+    }
+
+    fn validate_proxy(&self) -> AppResult<()> {
+        let proxy = match &self.proxy {
+            Some(proxy) => proxy,
+            None => return Ok(()),
+        };
+
+        let url = Url::parse(&proxy.url)
+            .map_err(|e| AppError::ValidationError(Message::key("config.invalid_proxy_url").arg("error", e)))?;
+        if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(AppError::ValidationError(
+                Message::key("config.unsupported_proxy_scheme").arg("scheme", url.scheme())
+            ));
+        }
+        if non_empty(&proxy.password).is_some() && non_empty(&proxy.username).is_none() {
+            return Err(AppError::ValidationError(Message::key("config.proxy_password_without_user")));
+        }
+        if non_empty(&self.ca_fingerprint).is_some() {
+            return Err(AppError::ValidationError(
+                Message::key("config.fingerprint_proxy")
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_auth(&self) -> AppResult<()> {
+        let username = non_empty(&self.username);
+        let password = non_empty(&self.password);
+
+        if password.is_some() && username.is_none() {
+            return Err(AppError::ValidationError(Message::key("config.password_without_user")));
+        }
+
+        let modes = [
+            username.is_some(),
+            non_empty(&self.api_key).is_some(),
+            non_empty(&self.bearer_token).is_some(),
+            non_empty(&self.client_cert_path).is_some(),
+        ];
+        if modes.iter().filter(|set| **set).count() > 1 {
+            return Err(AppError::ValidationError(
+                Message::key("config.multiple_auth")
+            ));
+        }
+
+        if let Some(api_key) = non_empty(&self.api_key) {
+            if let Some((id, key)) = api_key.split_once(':') {
+                if id.is_empty() || key.is_empty() {
+                    return Err(AppError::ValidationError(
+                        Message::key("config.invalid_api_key")
+                    ));
+                }
+            }
+        }
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    fn validate_tls(&self) -> AppResult<()> {
+        if self.verification_mode == VerificationMode::Certificate
+            && non_empty(&self.ca_cert_path).is_none()
+            && non_empty(&self.ca_fingerprint).is_none()
+        {
+            return Err(AppError::ValidationError(
+                Message::key("config.ca_required")
+            ));
+        }
+
+        if let Some(fingerprint) = non_empty(&self.ca_fingerprint) {
+            let hex = normalize_fingerprint(fingerprint);
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(AppError::ValidationError(
+                    Message::key("config.invalid_fingerprint")
+                ));
+            }
+            if non_empty(&self.cloud_id).is_some() {
+                return Err(AppError::ValidationError(Message::key("config.fingerprint_cloud")));
+            }
+            if self.hosts.iter().any(|host| !host.starts_with("https://")) {
+                return Err(AppError::ValidationError(
+                    Message::key("config.fingerprint_https")
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Endpoints the client talks to: the decoded Cloud ID endpoint, or every configured host.
+    pub fn node_urls(&self) -> AppResult<Vec<Url>> {
+        if let Some(cloud_id) = non_empty(&self.cloud_id) {
+            let cloud = CloudId::parse(cloud_id.trim())
+                .map_err(|e| AppError::ValidationError(Message::key("config.invalid_cloud_id").arg("error", e)))?;
+            return Ok(vec![cloud.url]);
+        }
+
+        self.hosts
+            .iter()
+            .map(|host| Url::parse(host))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::ValidationError(Message::key("config.invalid_host_url").arg("error", e)))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(30))
+    }
+
+    /// Every field holding a secret, so storage and export can encrypt them at rest.
+    pub fn secrets_mut(&mut self) -> Vec<&mut Option<String>> {
+        let mut secrets = vec![
+            &mut self.password,
+            &mut self.api_key,
+            &mut self.bearer_token,
+            &mut self.client_cert_password,
+        ];
+        if let Some(proxy) = &mut self.proxy {
+            secrets.push(&mut proxy.password);
+        }
+        if let Some(tunnel) = &mut self.ssh_tunnel {
+            secrets.push(&mut tunnel.password);
+            secrets.push(&mut tunnel.key_passphrase);
+        }
+        for (name, header) in &mut self.headers {
+            if is_secret_header(name, header) {
+                secrets.push(&mut header.value);
+            }
+        }
+        secrets
+    }
+
+    /// Copy with every secret masked, safe to log.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        for secret in config.secrets_mut() {
+            if secret.is_some() {
+                *secret = Some("********".to_string());
+            }
+        }
+        config
+    }
+
+    /// Credentials to send with every request, derived from the configured auth mode.
+    pub fn credentials(&self) -> Option<Credentials> {
+        if let Some(username) = non_empty(&self.username) {
+            let password = non_empty(&self.password).unwrap_or_default();
+            return Some(Credentials::Basic(username.to_string(), password.to_string()));
+        }
+
+        if let Some(api_key) = non_empty(&self.api_key) {
+            // Base64 never contains ':', so a colon means the raw id:api_key pair.
+            return Some(match api_key.split_once(':') {
+                Some((id, key)) => Credentials::ApiKey(id.to_string(), key.to_string()),
+                None => Credentials::EncodedApiKey(api_key.to_string()),
+            });
+        }
+
+        non_empty(&self.bearer_token).map(|token| Credentials::Bearer(token.to_string()))
+    }
+}
+
+/// Lowercase hex digest with any `:` separators removed.
+pub fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint.trim().replace(':', "").to_ascii_lowercase()
+}
+
+pub(crate) fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.trim().is_empty())
+}
@@ -0,0 +1,101 @@
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use crate::{
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// Prefix marking a value encrypted by [`MasterKey::encrypt`].
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+pub const SALT_LEN: usize = 16;
+
+/// Argon2id cost parameters, stored next to the salt so a key can be derived again
+/// after the defaults change.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// What secrets were derived with before the parameters were stored.
+    pub const LEGACY: Self = Self { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 };
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// AES-256-GCM key derived from the user's master password with Argon2id.
+#[derive(Clone)]
+pub struct MasterKey(Key<Aes256Gcm>);
+
+impl MasterKey {
+    pub fn derive(password: &str, salt: &[u8], kdf: &KdfParams) -> AppResult<Self> {
+        if password.is_empty() {
+            return Err(AppError::ValidationError(Message::key("vault.empty_password")));
+        }
+
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, None)
+            .map_err(|e| AppError::StateError(Message::key("vault.invalid_kdf").arg("error", e)))?;
+        let mut key = Key::<Aes256Gcm>::default();
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), salt, &mut key)
+            .map_err(|e| AppError::StateError(Message::key("vault.derive_failed").arg("error", e)))?;
+        Ok(Self(key))
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> AppResult<String> {
+        let cipher = Aes256Gcm::new(&self.0);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| AppError::StateError(Message::key("vault.encrypt_failed").arg("error", e)))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(payload)))
+    }
+
+    pub fn decrypt(&self, value: &str) -> AppResult<String> {
+        let encoded = value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or_else(|| AppError::StateError(Message::key("vault.not_encrypted")))?;
+        let payload = BASE64
+            .decode(encoded)
+            .map_err(|e| AppError::StateError(Message::key("vault.corrupted_secret").arg("error", e)))?;
+        if payload.len() < NONCE_LEN {
+            return Err(AppError::StateError(Message::key("vault.truncated_secret")));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = Aes256Gcm::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::StateError(Message::key("vault.decrypt_failed")))?;
+        String::from_utf8(plaintext)
+            .map_err(|e| AppError::StateError(Message::key("vault.corrupted_secret").arg("error", e)))
+    }
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+pub fn generate_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
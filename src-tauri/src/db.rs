@@ -1,103 +1,576 @@
-use crate::error::{AppError, AppResult};
-use rusqlite::params;
+use crate::{
+    config::ElasticsearchConfig,
+    crypto::{self, KdfParams, MasterKey},
+    error::{AppError, AppResult},
+    i18n::{Locale, Message},
+    inspector::RequestRecord,
+};
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tokio_rusqlite::Connection as AsyncConnection;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Known plaintext encrypted with the master key to check the password on unlock.
+const VAULT_VERIFIER: &str = "elastic-eye-vault";
+
+struct Migration {
+    version: i64,
+    description: &'static str,
+    sql: &'static str,
+}
+
+/// Forward-only schema migrations, applied in order. Never edit a released entry;
+/// append a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create connections table",
+        sql: "CREATE TABLE IF NOT EXISTS connections (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                host TEXT NOT NULL,
+                port INTEGER NOT NULL,
+                username TEXT,
+                password TEXT,
+                created_at INTEGER NOT NULL,
+                last_used_at INTEGER NOT NULL
+            );",
+    },
+    Migration {
+        version: 2,
+        description: "create vault table",
+        sql: "CREATE TABLE IF NOT EXISTS vault (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                salt BLOB NOT NULL,
+                verifier TEXT NOT NULL
+            );",
+    },
+    Migration {
+        version: 3,
+        description: "store the full connection config",
+        sql: "ALTER TABLE connections ADD COLUMN config TEXT;",
+    },
+    Migration {
+        version: 4,
+        description: "create settings table",
+        sql: "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+    },
+    Migration {
+        version: 5,
+        description: "create request log table",
+        sql: "CREATE TABLE IF NOT EXISTS request_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id TEXT NOT NULL,
+                record TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS request_log_connection ON request_log (connection_id, id);",
+    },
+    Migration {
+        version: 6,
+        description: "store key derivation parameters",
+        sql: "ALTER TABLE vault ADD COLUMN kdf TEXT;",
+    },
+];
+
+/// `settings` row holding the serialized [`AppSettings`].
+const APP_SETTINGS_KEY: &str = "app";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SavedConnection {
     pub id: String,
     pub name: String,
-    pub host: String,
-    pub port: u16,
-    pub username: Option<String>,
-    pub password: Option<String>,
+    pub config: ElasticsearchConfig,
     pub created_at: i64,
     pub last_used_at: i64,
+    #[serde(default)]
+    pub source: ConnectionSource,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionSource {
+    /// Saved in the local SQLite store.
+    #[default]
+    Database,
+    /// Defined in the profiles file or environment variables; read-only from the app.
+    Profile,
+}
+
+/// Raw `connections` row; `config` is NULL for rows saved before schema version 3.
+struct StoredConnection {
+    id: String,
+    name: String,
+    host: String,
+    port: u16,
+    username: Option<String>,
+    password: Option<String>,
+    config: Option<String>,
+    created_at: i64,
+    last_used_at: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub locale: Locale,
+    /// Keep the request inspector history in the database across restarts.
+    #[serde(default)]
+    pub persist_requests: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultStatus {
+    pub initialized: bool,
+    pub unlocked: bool,
+}
+
+struct Vault {
+    salt: Vec<u8>,
+    verifier: String,
+    kdf: KdfParams,
+}
+
+fn serialize_kdf(kdf: &KdfParams) -> AppResult<String> {
+    serde_json::to_string(kdf)
+        .map_err(|e| AppError::StateError(Message::key("vault.kdf_serialize_failed").arg("error", e)))
+}
+
+#[derive(Clone)]
 pub struct Database {
     conn: AsyncConnection,
+    key: Arc<RwLock<Option<MasterKey>>>,
 }
 
 impl Database {
     pub async fn new(path: PathBuf) -> AppResult<Self> {
-        let conn = AsyncConnection::open(path)
-            .await
-            .map_err(|e| AppError::StateError(format!("Failed to open database: {}", e)))?;
-
-        conn.call(|conn| {
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS connections (
-                    id TEXT PRIMARY KEY,
-                    name TEXT NOT NULL,
-                    host TEXT NOT NULL,
-                    port INTEGER NOT NULL,
-                    username TEXT,
-                    password TEXT,
-                    created_at INTEGER NOT NULL,
-                    last_used_at INTEGER NOT NULL
-                )",
-                [],
-            )
+        let conn = AsyncConnection::open(&path)
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.open_failed").arg("error", e)))?;
+
+        Self::migrate(&conn, &path).await?;
+
+        Ok(Database {
+            conn,
+            key: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// Brings the schema up to date, backing up the existing database file first.
+    async fn migrate(conn: &AsyncConnection, path: &Path) -> AppResult<()> {
+        let (current, has_tables) = conn
+            .call(|conn| {
+                conn.execute(
+                    "CREATE TABLE IF NOT EXISTS schema_version (
+                        version INTEGER PRIMARY KEY,
+                        description TEXT NOT NULL,
+                        applied_at INTEGER NOT NULL
+                    )",
+                    [],
+                )?;
+                let current: i64 = conn.query_row(
+                    "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+                    [],
+                    |row| row.get(0),
+                )?;
+                let tables: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name <> 'schema_version'",
+                    [],
+                    |row| row.get(0),
+                )?;
+                Ok((current, tables > 0))
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.schema_read_failed").arg("error", e)))?;
+
+        let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+        if current > latest {
+            return Err(AppError::StateError(
+                Message::key("db.schema_too_new").arg("version", current).arg("latest", latest)
+            ));
+        }
+        if current == latest {
+            return Ok(());
+        }
+
+        if has_tables {
+            let backup = path.with_extension(format!("db.v{}.bak", current));
+            let backup_path = backup.to_string_lossy().to_string();
+            conn.call(move |conn| {
+                // VACUUM INTO refuses to overwrite, so drop a stale backup of the same version.
+                let _ = std::fs::remove_file(&backup_path);
+                conn.execute("VACUUM INTO ?", params![backup_path])
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.backup_failed").arg("error", e)))?;
+        }
+
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            conn.call(move |conn| {
+                let tx = conn.transaction()?;
+                tx.execute_batch(migration.sql)?;
+                tx.execute(
+                    "INSERT INTO schema_version (version, description, applied_at) VALUES (?, ?, ?)",
+                    params![migration.version, migration.description, chrono::Utc::now().timestamp()],
+                )?;
+                tx.commit()
+            })
+            .await
+            .map_err(|e| AppError::StateError(
+                Message::key("db.migration_failed")
+                    .arg("version", migration.version)
+                    .arg("description", migration.description)
+                    .arg("error", e)
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    async fn load_vault(&self) -> AppResult<Option<Vault>> {
+        let row: Option<(Vec<u8>, String, Option<String>)> = self.conn
+            .call(|conn| {
+                conn.query_row("SELECT salt, verifier, kdf FROM vault WHERE id = 1", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .optional()
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("vault.read_failed").arg("error", e)))?;
+
+        row.map(|(salt, verifier, kdf)| {
+            let kdf = match kdf {
+                Some(json) => serde_json::from_str(&json)
+                    .map_err(|e| AppError::StateError(Message::key("vault.corrupted").arg("error", e)))?,
+                // Vaults created before the parameters were stored.
+                None => KdfParams::LEGACY,
+            };
+            Ok(Vault { salt, verifier, kdf })
         })
-        .await
-        .map_err(|e| AppError::StateError(format!("Failed to create table: {}", e)))?;
+        .transpose()
+    }
 
-        Ok(Database { conn })
+    pub async fn vault_status(&self) -> AppResult<VaultStatus> {
+        Ok(VaultStatus {
+            initialized: self.load_vault().await?.is_some(),
+            unlocked: self.key.read().await.is_some(),
+        })
+    }
+
+    /// Unlocks the secret store, creating it with `master_password` on first use.
+    /// Any secrets still stored in plaintext are encrypted afterwards.
+    pub async fn unlock(&self, master_password: String) -> AppResult<()> {
+        let key = match self.load_vault().await? {
+            Some(vault) => {
+                let key = MasterKey::derive(&master_password, &vault.salt, &vault.kdf)?;
+                match key.decrypt(&vault.verifier) {
+                    Ok(value) if value == VAULT_VERIFIER => key,
+                    _ => return Err(AppError::ValidationError(Message::key("vault.invalid_password"))),
+                }
+            }
+            None => {
+                let salt = crypto::generate_salt();
+                let kdf = KdfParams::default();
+                let key = MasterKey::derive(&master_password, &salt, &kdf)?;
+                let verifier = key.encrypt(VAULT_VERIFIER)?;
+                let kdf = serialize_kdf(&kdf)?;
+                self.conn
+                    .call(move |conn| {
+                        conn.execute(
+                            "INSERT INTO vault (id, salt, verifier, kdf) VALUES (1, ?, ?, ?)",
+                            params![salt.to_vec(), verifier, kdf],
+                        )
+                    })
+                    .await
+                    .map_err(|e| AppError::StateError(Message::key("vault.create_failed").arg("error", e)))?;
+                key
+            }
+        };
+
+        *self.key.write().await = Some(key.clone());
+        self.encrypt_plaintext_secrets(&key).await
+    }
+
+    /// Re-encrypts every stored secret under a key derived from `new_password`.
+    pub async fn change_master_password(&self, current_password: String, new_password: String) -> AppResult<()> {
+        let vault = self.load_vault().await?
+            .ok_or_else(|| AppError::StateError(Message::key("vault.not_initialized")))?;
+        let old_key = MasterKey::derive(&current_password, &vault.salt, &vault.kdf)?;
+        if !matches!(old_key.decrypt(&vault.verifier), Ok(value) if value == VAULT_VERIFIER) {
+            return Err(AppError::ValidationError(Message::key("vault.invalid_password")));
+        }
+
+        // Re-keying also moves the vault to the current default parameters.
+        let new_salt = crypto::generate_salt();
+        let new_kdf = KdfParams::default();
+        let new_key = MasterKey::derive(&new_password, &new_salt, &new_kdf)?;
+        let new_verifier = new_key.encrypt(VAULT_VERIFIER)?;
+        let new_kdf = serialize_kdf(&new_kdf)?;
+
+        let updates = self.rewrite_secrets(|value| {
+            let plaintext = if crypto::is_encrypted(value) {
+                old_key.decrypt(value)?
+            } else {
+                value.to_string()
+            };
+            Ok(Some(new_key.encrypt(&plaintext)?))
+        }).await?;
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                for (id, password, config) in &updates {
+                    tx.execute(
+                        "UPDATE connections SET password = ?, config = ? WHERE id = ?",
+                        params![password, config, id],
+                    )?;
+                }
+                tx.execute(
+                    "UPDATE vault SET salt = ?, verifier = ?, kdf = ? WHERE id = 1",
+                    params![new_salt.to_vec(), new_verifier, new_kdf],
+                )?;
+                tx.commit()
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("vault.rekey_failed").arg("error", e)))?;
+
+        *self.key.write().await = Some(new_key);
+        Ok(())
+    }
+
+    /// Applies `rewrite` to every stored secret and returns the rows that changed as
+    /// `(id, password, config)`. `rewrite` returns `None` to keep a value as is.
+    async fn rewrite_secrets<F>(&self, rewrite: F) -> AppResult<Vec<(String, Option<String>, Option<String>)>>
+    where
+        F: Fn(&str) -> AppResult<Option<String>>,
+    {
+        let rows = self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare("SELECT id, password, config FROM connections")?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, Option<String>>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("vault.read_secrets_failed").arg("error", e)))?;
+
+        let mut updates = Vec::new();
+        for (id, mut password, config) in rows {
+            let mut changed = false;
+            if let Some(value) = password.as_deref() {
+                if let Some(rewritten) = rewrite(value)? {
+                    password = Some(rewritten);
+                    changed = true;
+                }
+            }
+
+            let config = match config {
+                Some(json) => {
+                    let mut config: ElasticsearchConfig = serde_json::from_str(&json)
+                        .map_err(|e| AppError::StateError(Message::key("db.corrupted_config").arg("error", e)))?;
+                    for secret in config.secrets_mut().into_iter().flatten() {
+                        if let Some(rewritten) = rewrite(secret)? {
+                            *secret = rewritten;
+                            changed = true;
+                        }
+                    }
+                    Some(serde_json::to_string(&config)
+                        .map_err(|e| AppError::StateError(Message::key("db.config_serialize_failed").arg("error", e)))?)
+                }
+                None => None,
+            };
+
+            if changed {
+                updates.push((id, password, config));
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Migrates rows written before encryption was introduced.
+    async fn encrypt_plaintext_secrets(&self, key: &MasterKey) -> AppResult<()> {
+        let updates = self.rewrite_secrets(|value| {
+            if crypto::is_encrypted(value) {
+                Ok(None)
+            } else {
+                Ok(Some(key.encrypt(value)?))
+            }
+        }).await?;
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                for (id, password, config) in &updates {
+                    tx.execute(
+                        "UPDATE connections SET password = ?, config = ? WHERE id = ?",
+                        params![password, config, id],
+                    )?;
+                }
+                tx.commit()
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("vault.encrypt_stored_failed").arg("error", e)))
+    }
+
+    async fn unlocked_key(&self) -> AppResult<MasterKey> {
+        self.key
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| AppError::StateError(Message::key("vault.locked")))
     }
 
     pub async fn save_connection(&self, connection: SavedConnection) -> AppResult<()> {
+        self.save_connections(vec![connection]).await
+    }
+
+    /// Saves every connection in one transaction, so either all of them are stored or none.
+    pub async fn save_connections(&self, connections: Vec<SavedConnection>) -> AppResult<()> {
+        let mut rows = Vec::with_capacity(connections.len());
+        for connection in connections {
+            let mut config = connection.config;
+            if config.secrets_mut().iter().any(|secret| secret.is_some()) {
+                let key = self.unlocked_key().await?;
+                for secret in config.secrets_mut().into_iter().flatten() {
+                    *secret = key.encrypt(secret)?;
+                }
+            }
+
+            // host/port/username are kept for display and for older app versions.
+            let first = config.node_urls()?.into_iter().next()
+                .ok_or_else(|| AppError::ValidationError(Message::key("config.no_host")))?;
+            let host = first.host_str().unwrap_or_default().to_string();
+            let port = first.port_or_known_default().unwrap_or(9200);
+            let username = config.username.clone();
+            let config = serde_json::to_string(&config)
+                .map_err(|e| AppError::StateError(Message::key("db.config_serialize_failed").arg("error", e)))?;
+            rows.push((connection.id, connection.name, host, port, username, config, connection.created_at, connection.last_used_at));
+        }
+
         self.conn
             .call(move |conn| {
-                conn.execute(
-                    "INSERT OR REPLACE INTO connections (
-                        id, name, host, port, username, password, created_at, last_used_at
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                    params![
-                        connection.id,
-                        connection.name,
-                        connection.host,
-                        connection.port,
-                        connection.username,
-                        connection.password,
-                        connection.created_at,
-                        connection.last_used_at,
-                    ],
-                )
+                let tx = conn.transaction()?;
+                for (id, name, host, port, username, config, created_at, last_used_at) in &rows {
+                    tx.execute(
+                        "INSERT INTO connections (
+                            id, name, host, port, username, password, config, created_at, last_used_at
+                        ) VALUES (?, ?, ?, ?, ?, NULL, ?, ?, ?)
+                        ON CONFLICT(id) DO UPDATE SET
+                            name = excluded.name,
+                            host = excluded.host,
+                            port = excluded.port,
+                            username = excluded.username,
+                            password = NULL,
+                            config = excluded.config,
+                            last_used_at = excluded.last_used_at",
+                        params![id, name, host, port, username, config, created_at, last_used_at],
+                    )?;
+                }
+                tx.commit()
             })
             .await
-            .map_err(|e| AppError::StateError(format!("Failed to save connection: {}", e)))?;
+            .map_err(|e| AppError::StateError(Message::key("db.save_failed").arg("error", e)))?;
 
         Ok(())
     }
 
+    /// Every saved connection. While the store is locked, secrets come back masked
+    /// so the list can still be shown.
     pub async fn list_connections(&self) -> AppResult<Vec<SavedConnection>> {
-        self.conn
+        let key = self.key.read().await.clone();
+        self.load_connections(key.as_ref()).await
+    }
+
+    /// Every saved connection with its secrets decrypted; fails while the store is locked.
+    pub async fn decrypted_connections(&self) -> AppResult<Vec<SavedConnection>> {
+        let key = self.unlocked_key().await?;
+        self.load_connections(Some(&key)).await
+    }
+
+    async fn load_connections(&self, key: Option<&MasterKey>) -> AppResult<Vec<SavedConnection>> {
+        let rows = self.conn
             .call(|conn| {
                 let mut stmt = conn.prepare(
-                    "SELECT id, name, host, port, username, password, created_at, last_used_at 
-                     FROM connections 
+                    "SELECT id, name, host, port, username, password, config, created_at, last_used_at
+                     FROM connections
                      ORDER BY last_used_at DESC",
                 )?;
-                let connections = stmt
+                let rows = stmt
                     .query_map([], |row| {
-                        Ok(SavedConnection {
+                        Ok(StoredConnection {
                             id: row.get(0)?,
                             name: row.get(1)?,
                             host: row.get(2)?,
                             port: row.get(3)?,
                             username: row.get(4)?,
                             password: row.get(5)?,
-                            created_at: row.get(6)?,
-                            last_used_at: row.get(7)?,
+                            config: row.get(6)?,
+                            created_at: row.get(7)?,
+                            last_used_at: row.get(8)?,
                         })
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
-                Ok(connections)
+                Ok(rows)
             })
             .await
-            .map_err(|e| AppError::StateError(format!("Failed to list connections: {}", e)))
+            .map_err(|e| AppError::StateError(Message::key("db.list_failed").arg("error", e)))?;
+
+        let mut connections = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut config = match row.config {
+                Some(json) => serde_json::from_str::<ElasticsearchConfig>(&json)
+                    .map_err(|e| AppError::StateError(Message::key("db.corrupted_config").arg("error", e)))?,
+                // Rows saved before the full config was persisted only know host and port.
+                None => ElasticsearchConfig {
+                    hosts: vec![format!("http://{}:{}", row.host, row.port)],
+                    username: row.username,
+                    password: row.password,
+                    ..ElasticsearchConfig::default()
+                },
+            };
+            match key {
+                Some(key) => {
+                    for secret in config.secrets_mut().into_iter().flatten() {
+                        *secret = key.decrypt(secret)?;
+                    }
+                }
+                None => config = config.redacted(),
+            }
+
+            connections.push(SavedConnection {
+                id: row.id,
+                name: row.name,
+                config,
+                created_at: row.created_at,
+                last_used_at: row.last_used_at,
+                source: ConnectionSource::Database,
+            });
+        }
+
+        Ok(connections)
+    }
+
+    pub async fn get_connection(&self, id: &str) -> AppResult<SavedConnection> {
+        self.decrypted_connections()
+            .await?
+            .into_iter()
+            .find(|connection| connection.id == id)
+            .ok_or_else(|| AppError::ValidationError(Message::key("db.connection_not_found").arg("id", id)))
     }
 
     pub async fn update_last_used(&self, id: String) -> AppResult<()> {
@@ -109,7 +582,86 @@ impl Database {
                 )
             })
             .await
-            .map_err(|e| AppError::StateError(format!("Failed to update last used: {}", e)))?;
+            .map_err(|e| AppError::StateError(Message::key("db.touch_failed").arg("error", e)))?;
+        Ok(())
+    }
+
+    pub async fn load_settings(&self) -> AppResult<AppSettings> {
+        let value: Option<String> = self.conn
+            .call(|conn| {
+                conn.query_row("SELECT value FROM settings WHERE key = ?", params![APP_SETTINGS_KEY], |row| row.get(0))
+                    .optional()
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.settings_read_failed").arg("error", e)))?;
+        match value {
+            Some(value) => serde_json::from_str(&value)
+                .map_err(|e| AppError::StateError(Message::key("db.settings_invalid").arg("error", e))),
+            None => Ok(AppSettings::default()),
+        }
+    }
+
+    pub async fn save_settings(&self, settings: &AppSettings) -> AppResult<()> {
+        let value = serde_json::to_string(settings)
+            .map_err(|e| AppError::StateError(Message::key("db.settings_serialize_failed").arg("error", e)))?;
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO settings (key, value) VALUES (?, ?)
+                     ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                    params![APP_SETTINGS_KEY, value],
+                )
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.settings_save_failed").arg("error", e)))?;
+        Ok(())
+    }
+
+    /// Appends `record` to the request log, keeping the newest `keep` rows of its connection.
+    pub async fn save_request(&self, record: &RequestRecord, keep: usize) -> AppResult<()> {
+        let connection_id = record.connection_id.clone();
+        let value = serde_json::to_string(record)
+            .map_err(|e| AppError::StateError(Message::key("db.request_serialize_failed").arg("error", e)))?;
+        self.conn
+            .call(move |conn| {
+                conn.execute(
+                    "INSERT INTO request_log (connection_id, record) VALUES (?, ?)",
+                    params![connection_id, value],
+                )?;
+                conn.execute(
+                    "DELETE FROM request_log WHERE connection_id = ?1 AND id NOT IN (
+                        SELECT id FROM request_log WHERE connection_id = ?1 ORDER BY id DESC LIMIT ?2
+                    )",
+                    params![connection_id, keep as i64],
+                )
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.request_save_failed").arg("error", e)))?;
+        Ok(())
+    }
+
+    /// Persisted requests of every connection, oldest first. Unreadable rows are skipped.
+    pub async fn load_requests(&self) -> AppResult<Vec<RequestRecord>> {
+        let rows: Vec<String> = self.conn
+            .call(|conn| {
+                let mut stmt = conn.prepare("SELECT record FROM request_log ORDER BY id")?;
+                let rows = stmt
+                    .query_map([], |row| row.get(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.request_log_read_failed").arg("error", e)))?;
+        Ok(rows.iter().filter_map(|row| serde_json::from_str(row).ok()).collect())
+    }
+
+    pub async fn clear_requests(&self, connection_id: String) -> AppResult<()> {
+        self.conn
+            .call(move |conn| {
+                conn.execute("DELETE FROM request_log WHERE connection_id = ?", params![connection_id])
+            })
+            .await
+            .map_err(|e| AppError::StateError(Message::key("db.request_log_clear_failed").arg("error", e)))?;
         Ok(())
     }
 
@@ -119,7 +671,7 @@ impl Database {
                 conn.execute("DELETE FROM connections WHERE id = ?", params![id])
             })
             .await
-            .map_err(|e| AppError::StateError(format!("Failed to delete connection: {}", e)))?;
+            .map_err(|e| AppError::StateError(Message::key("db.delete_failed").arg("error", e)))?;
         Ok(())
     }
 } 
\ No newline at end of file
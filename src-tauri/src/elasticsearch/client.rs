@@ -1,6 +1,8 @@
 use elasticsearch::{
     Elasticsearch,
-    http::transport::Transport,
+    http::transport::{
+        TransportBuilder, SingleNodeConnectionPool, CloudConnectionPool,
+    },
     cat::CatIndicesParts,
     cluster::{ClusterHealthParts, ClusterStatsParts},
     nodes::NodesInfoParts,
@@ -12,13 +14,37 @@ use elasticsearch::{
     },
     params::Bytes,
     Error,
+    SearchParts,
+    ScrollParts,
+    ClearScrollParts,
     IndexParts,
 };
+use elasticsearch::http::{
+    headers::{HeaderMap, HeaderName, HeaderValue, X_OPAQUE_ID},
+    request::JsonBody,
+    Method,
+};
 use serde_json::{Value, json};
 use crate::{
-    config::ElasticsearchConfig,
-    error::{AppError, AppResult},
+    config::{non_empty, ElasticsearchConfig, VerificationMode},
+    elasticsearch::{
+        pool::{self, NodePool, SnifferGuard},
+        proxy, ssh::SshTunnel, tls::{self, PinnedRelay},
+        version::{Distribution, Feature, ServerVersion},
+    },
+    error::{AppError, AppResult, ResponseError},
+    i18n::Message,
+    inspector::{self, RequestInspector, RequestRecord},
+};
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
 };
+use std::time::{Duration, Instant};
+use url::{Position, Url};
+use tracing::{debug, error, info, trace, warn};
+use elasticsearch::http::response::Response;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -43,6 +69,7 @@ pub struct ClusterInfo {
     pub initializing_shards: i64,
     pub unassigned_shards: i64,
     pub version: String,
+    pub distribution: Distribution,
     pub nodes: Vec<NodeInfo>,
 }
 
@@ -138,188 +165,529 @@ pub struct ESClient {
     client: Elasticsearch,
     config: ElasticsearchConfig,
     connection_info: ConnectionInfo,
+    /// Liveness as last seen by the health monitor, reported as `ConnectionInfo::is_active`.
+    active: Arc<AtomicBool>,
+    tunnel: Option<Arc<SshTunnel>>,
+    relay: Option<Arc<PinnedRelay>>,
+    pool: Option<NodePool>,
+    sniffer: Option<Arc<SnifferGuard>>,
+    version: Option<ServerVersion>,
+    inspector: Option<Arc<RequestInspector>>,
+}
+
+/// Failures without a response that [`ESClient::send`] retries, by what repeating the
+/// request could cost. Failures to connect are always retried as nothing was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    /// Idempotent requests: timeouts and transport failures.
+    Always,
+    /// Searches: transport failures, but not timeouts, which would re-run a long query.
+    Read,
+    /// Requests creating or changing something: only failures to connect.
+    ConnectOnly,
+}
+
+/// What a request asks for, as recorded by the request inspector. Once a response
+/// arrives its URL is recorded instead, as it also carries the node and query string.
+struct Endpoint<'a> {
+    method: Method,
+    path: String,
+    body: Option<&'a Value>,
+    /// Top-level body fields never recorded, on top of secret-looking keys.
+    redact: &'static [&'static str],
+    retry: Retry,
+}
+
+impl<'a> Endpoint<'a> {
+    /// POST requests are taken as not idempotent unless marked with [`Endpoint::read`].
+    /// DELETE is idempotent, but repeating one that already took effect fails with a 404.
+    fn new(method: Method, path: impl Into<String>) -> Self {
+        let retry = match method {
+            Method::Post | Method::Delete => Retry::ConnectOnly,
+            _ => Retry::Always,
+        };
+        Self { method, path: path.into(), body: None, redact: &[], retry }
+    }
+
+    fn body(mut self, body: &'a Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    fn redact(mut self, fields: &'static [&'static str]) -> Self {
+        self.redact = fields;
+        self
+    }
+
+    /// Marks a POST that only reads, such as a search.
+    fn read(mut self) -> Self {
+        self.retry = Retry::Read;
+        self
+    }
+
+    fn retries(&self, error: &Error) -> bool {
+        let connect_failed = std::error::Error::source(error)
+            .and_then(|source| source.downcast_ref::<reqwest::Error>())
+            .is_some_and(reqwest::Error::is_connect);
+        match self.retry {
+            Retry::Always => true,
+            Retry::Read => connect_failed || !error.is_timeout(),
+            Retry::ConnectOnly => connect_failed,
+        }
+    }
 }
 
 impl ESClient {
     pub async fn new(config: ElasticsearchConfig, connection_info: ConnectionInfo) -> AppResult<Self> {
-        println!("Attempting to create new ES client with config: {:?}", config);
-        
+        Self::connect(config, connection_info, None).await
+    }
+
+    /// Like [`ESClient::new`], recording every request with `inspector`.
+    pub async fn with_inspector(
+        config: ElasticsearchConfig,
+        connection_info: ConnectionInfo,
+        inspector: Arc<RequestInspector>,
+    ) -> AppResult<Self> {
+        Self::connect(config, connection_info, Some(inspector)).await
+    }
+
+    async fn connect(
+        config: ElasticsearchConfig,
+        connection_info: ConnectionInfo,
+        inspector: Option<Arc<RequestInspector>>,
+    ) -> AppResult<Self> {
+        debug!(config = ?config.redacted(), "Creating client");
         config.validate()?;
-        println!("Config validation passed");
 
-        let hosts: Vec<&str> = config.hosts.iter().map(|s| s.as_str()).collect();
-        println!("Using hosts: {:?}", hosts);
-        
-        let transport = if hosts.len() == 1 {
-            println!("Creating single node transport");
-            Transport::single_node(hosts[0])
-        } else {
-            println!("Creating multi-node transport");
-            Transport::static_node_list(hosts)
-        }.map_err(|e| {
-            println!("Transport creation failed: {}", e);
-            AppError::ConnectionError(format!(
-                "传输层错误: {}. 详细信息: {:?}", 
-                e.to_string(),
-                e
-            ))
-        })?;
-
-        println!("Transport created successfully");
-        let client = Elasticsearch::new(transport);
-        
-        match client.ping().send().await {
-            Ok(_) => {
-                println!("Ping successful, connection established");
-                Ok(Self { client, config, connection_info })
-            },
-            Err(e) => {
-                println!("Ping failed: {}", e);
-                Err(AppError::ConnectionError(format!(
-                    "无法连接到 Elasticsearch: {}。\n详细错误: {:?}\n请检查:\n1. 服务器地址是否正确\n2. Elasticsearch 是否正在运行\n3. 网络连接是否正常",
-                    e.to_string(),
-                    e
-                )))
+        // Behind a bastion the transport talks to the local ends of the tunnel. Validation
+        // keeps https hosts off full verification there unless the pinned relay, which
+        // still checks the configured hostnames, sits in between.
+        let mut routed = config.clone();
+        let tunnel = match &config.ssh_tunnel {
+            Some(ssh) => {
+                let (tunnel, local_hosts) = SshTunnel::open(ssh, &config.node_urls()?, config.timeout()).await?;
+                info!(host = %ssh.host, port = ssh.port, "SSH tunnel established");
+                routed.hosts = local_hosts;
+                Some(Arc::new(tunnel))
+            }
+            None => None,
+        };
+
+        let result = match Self::relay(&config, &mut routed).await {
+            Ok(relay) => Self::establish(config, routed, connection_info, tunnel.clone(), relay, inspector).await,
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            // Dropping the tunnel only stops its forwards; the SSH session needs closing.
+            if let Some(tunnel) = &tunnel {
+                tunnel.close().await;
             }
         }
+        result
     }
 
-    pub fn get_connection_info(&self) -> &ConnectionInfo {
-        &self.connection_info
+    /// With a pinned CA fingerprint, points the `routed` hosts at a relay that makes the
+    /// TLS connections itself. The transport then speaks plain http to the relay, so it
+    /// is left with no TLS settings of its own.
+    async fn relay(config: &ElasticsearchConfig, routed: &mut ElasticsearchConfig) -> AppResult<Option<Arc<PinnedRelay>>> {
+        if non_empty(&config.ca_fingerprint).is_none() {
+            return Ok(None);
+        }
+
+        let nodes: Vec<(Url, Url)> = config.node_urls()?.into_iter().zip(routed.node_urls()?).collect();
+        let (relay, local_hosts) = PinnedRelay::open(config, &nodes).await?;
+        debug!(hosts = ?local_hosts, "Relaying TLS with the pinned fingerprint");
+        routed.hosts = local_hosts;
+        routed.verification_mode = VerificationMode::Full;
+        routed.ca_fingerprint = None;
+        routed.ca_cert_path = None;
+        routed.client_cert_path = None;
+        routed.client_cert_password = None;
+        Ok(Some(Arc::new(relay)))
     }
 
-    pub async fn test_connection(&self) -> AppResult<bool> {
-        println!("Testing connection to: {:?}", self.config.hosts);
-        match self.client.ping().send().await {
+    /// Builds the transport over `routed` hosts and checks the cluster answers.
+    async fn establish(
+        config: ElasticsearchConfig,
+        routed: ElasticsearchConfig,
+        connection_info: ConnectionInfo,
+        tunnel: Option<Arc<SshTunnel>>,
+        relay: Option<Arc<PinnedRelay>>,
+        inspector: Option<Arc<RequestInspector>>,
+    ) -> AppResult<Self> {
+        let (builder, pool) = Self::transport_builder(&routed)?;
+        let transport = builder
+            .build()
+            .map_err(|e| {
+                error!(error = %e, "Transport creation failed");
+                AppError::ConnectionError(Message::key("connection.transport_failed").arg("error", e))
+            })?;
+
+        let mut es_client = Self {
+            client: Elasticsearch::new(transport),
+            config,
+            connection_info,
+            active: Arc::new(AtomicBool::new(false)),
+            tunnel,
+            relay,
+            pool,
+            sniffer: None,
+            version: None,
+            inspector,
+        };
+
+        match es_client.send(Endpoint::new(Method::Head, "/"), || async { es_client.client.ping().send().await }).await {
             Ok(_) => {
-                println!("Connection test successful");
-                Ok(true)
+                es_client.set_active(true);
+                let version = es_client.detect_version().await?;
+                info!(version = %version, "Connected");
+                es_client.version = Some(version);
+                es_client.start_sniffing().await;
+                Ok(es_client)
+            },
+            Err(AppError::ResponseError(e)) => {
+                warn!(status = e.status, "Ping rejected");
+                Err(AppError::ResponseError(e))
             },
             Err(e) => {
-                println!("Connection test failed: {}", e);
-                Err(AppError::ConnectionError(format!(
-                    "连接测试失败: {}。详细信息: {:?}",
-                    e.to_string(),
-                    e
-                )))
+                warn!(error = %e, "Ping failed");
+                Err(AppError::ConnectionError(Message::key("connection.ping_failed").arg("error", e.detail())))
             }
         }
     }
 
-    pub fn get_client(&self) -> &Elasticsearch {
-        &self.client
+    fn transport_builder(config: &ElasticsearchConfig) -> AppResult<(TransportBuilder, Option<NodePool>)> {
+        let urls = config.node_urls()?;
+        debug!(hosts = ?urls, "Using hosts");
+
+        let nodes = urls.clone();
+        let mut node_pool = None;
+        let mut builder = if let Some(cloud_id) = non_empty(&config.cloud_id) {
+            debug!("Creating Elastic Cloud transport");
+            let pool = CloudConnectionPool::new(cloud_id.trim())
+                .map_err(|e| AppError::ValidationError(Message::key("config.invalid_cloud_id").arg("error", e)))?;
+            TransportBuilder::new(pool)
+        } else if urls.len() == 1 && !config.pool.sniff {
+            debug!("Creating single node transport");
+            TransportBuilder::new(SingleNodeConnectionPool::new(urls[0].clone()))
+        } else {
+            debug!(selection = ?config.pool.selection, "Creating multi-node transport");
+            let pool = NodePool::new(urls, config.pool.selection);
+            node_pool = Some(pool.clone());
+            TransportBuilder::new(pool)
+        };
+
+        builder = builder.timeout(config.timeout());
+        let headers = config.header_map()?;
+        if !headers.is_empty() {
+            builder = builder.headers(headers);
+        }
+        if let Some(credentials) = config.credentials() {
+            builder = builder.auth(credentials);
+        }
+
+        builder = proxy::configure(builder, config, &nodes)?;
+
+        Ok((tls::configure(builder, config)?, node_pool))
     }
 
-    pub fn get_config(&self) -> &ElasticsearchConfig {
-        &self.config
+    async fn detect_version(&self) -> AppResult<ServerVersion> {
+        let response = self
+            .send(Endpoint::new(Method::Get, "/"), || async move { self.client.info().send().await })
+            .await?;
+        let root = response.json::<Value>()
+            .await?;
+        ServerVersion::parse(&root)
     }
 
-    pub async fn list_indices(&self) -> AppResult<Vec<IndexInfo>> {
-        let response = self.client
-            .cat()
-            .indices(CatIndicesParts::None)
-            .format("json")
-            .v(true)
-            .bytes(Bytes::B)
-            .send()
-            .await
-            .map_err(|e| AppError::ElasticsearchError(e.to_string()))?;
+    /// Version detected on connect.
+    pub fn server_version(&self) -> AppResult<&ServerVersion> {
+        self.version
+            .as_ref()
+            .ok_or_else(|| AppError::StateError(Message::key("version.not_detected")))
+    }
 
-        let indices: Vec<serde_json::Value> = response.json()
-            .await
-            .map_err(|e| AppError::ElasticsearchError(e.to_string()))?;
+    /// Runs the initial sniff and schedules periodic ones when sniffing is enabled.
+    async fn start_sniffing(&mut self) {
+        let pool = match &self.pool {
+            Some(pool) if self.config.pool.sniff => pool.clone(),
+            _ => return,
+        };
 
-        let index_infos = indices.into_iter()
-            .map(|index| {
-                IndexInfo {
-                    name: index["index"].as_str().unwrap_or_default().to_string(),
-                    docs_count: index["docs.count"].as_str()
-                        .and_then(|s| s.parse::<i64>().ok())
-                        .unwrap_or(0),
-                    size_in_bytes: index["store.size"].as_str()
-                        .and_then(|s| s.parse::<i64>().ok())
-                        .unwrap_or(0),
-                    health: index["health"].as_str()
-                        .unwrap_or("unknown").to_string(),
-                    status: index["status"].as_str()
-                        .unwrap_or("unknown").to_string(),
-                    uuid: index["uuid"].as_str()
-                        .unwrap_or("unknown").to_string(),
-                    creation_date: index["creation.date"].as_str()
-                        .unwrap_or("0").to_string(),
+        let scheme = self.sniff_scheme();
+        if let Err(e) = pool::sniff(&self.client, &pool, &self.config.pool, &scheme, self.config.timeout()).await {
+            warn!(error = %e, "Initial sniff failed, keeping configured hosts");
+        }
+
+        if let Some(interval) = self.config.pool.sniff_interval_secs {
+            let client = self.client.clone();
+            let config = self.config.pool.clone();
+            let timeout = self.config.timeout();
+            let task = tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs(interval)).await;
+                    if let Err(e) = pool::sniff(&client, &pool, &config, &scheme, timeout).await {
+                        warn!(error = %e, "Periodic sniff failed");
+                    }
                 }
-            })
-            .collect();
+            });
+            self.sniffer = Some(Arc::new(SnifferGuard(task)));
+        }
+    }
 
-        Ok(index_infos)
+    /// Sniffed nodes are addressed with the scheme of the first configured host.
+    fn sniff_scheme(&self) -> String {
+        self.config
+            .hosts
+            .first()
+            .and_then(|host| Url::parse(host).ok())
+            .map(|url| url.scheme().to_string())
+            .unwrap_or_else(|| "http".to_string())
     }
 
-    pub async fn get_cluster_info(&self) -> AppResult<ClusterInfo> {
-        println!("Starting to fetch cluster info");
-        // Get cluster health
-        println!("Fetching cluster health...");
-        let health = match self.client
-            .cluster()
-            .health(ClusterHealthParts::None)
-            .send()
-            .await {
-                Ok(response) => {
-                    println!("Successfully received health response");
-                    response
-                },
-                Err(e) => {
-                    println!("Failed to get cluster health: {:?}", e);
-                    return Err(AppError::ElasticsearchError(e.to_string()));
+    /// Reacts to a transport failure: moves sticky pools off the failed node and
+    /// re-sniffs in the background when configured to.
+    fn on_transport_failure(&self) {
+        let pool = match &self.pool {
+            Some(pool) => pool.clone(),
+            None => return,
+        };
+        pool.mark_failed();
+
+        if self.config.pool.sniff && self.config.pool.sniff_on_failure {
+            let client = self.client.clone();
+            let config = self.config.pool.clone();
+            let scheme = self.sniff_scheme();
+            let timeout = self.config.timeout();
+            tokio::spawn(async move {
+                if let Err(e) = pool::sniff(&client, &pool, &config, &scheme, timeout).await {
+                    warn!(error = %e, "Sniff after failure failed");
                 }
-            };
+            });
+        }
+    }
 
-        let health_response = match health.json::<serde_json::Value>().await {
-            Ok(json) => {
-                println!("Successfully parsed health response: {:?}", json);
-                json
-            },
-            Err(e) => {
-                println!("Failed to parse health response: {:?}", e);
-                return Err(AppError::ElasticsearchError(e.to_string()));
-            }
-        };
+    /// Nodes requests are currently spread over.
+    pub fn active_nodes(&self) -> Vec<String> {
+        match &self.pool {
+            Some(pool) => pool.urls(),
+            None => self.connection_info.hosts.clone(),
+        }
+    }
 
-        // Get nodes info
-        println!("Fetching nodes info...");
-        let nodes = match self.client
-            .nodes()
-            .info(NodesInfoParts::None)
-            .send()
-            .await {
-                Ok(response) => {
-                    println!("Successfully received nodes response");
-                    response
-                },
+    /// Sends a request built by `request`, retrying the configured status codes and,
+    /// as far as the endpoint allows, transport failures with exponential backoff. A
+    /// non-2xx response that is not retried becomes [`AppError::ResponseError`].
+    async fn send<F, Fut>(&self, endpoint: Endpoint<'_>, request: F) -> AppResult<Response>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Response, Error>>,
+    {
+        let policy = &self.config.retry;
+        let mut attempt = 0;
+        loop {
+            let result = self.recorded(&endpoint, request()).await;
+            let retryable = match &result {
+                Ok(response) => policy.retry_on_status.contains(&response.status_code().as_u16()),
                 Err(e) => {
-                    println!("Failed to get nodes info: {:?}", e);
-                    return Err(AppError::ElasticsearchError(e.to_string()));
+                    let transport_failure = e.is_timeout() || e.status_code().is_none();
+                    if transport_failure {
+                        self.on_transport_failure();
+                    }
+                    transport_failure && endpoint.retries(e)
                 }
             };
+            if !retryable || attempt >= policy.max_retries {
+                return ensure_success(result?).await;
+            }
 
-        let nodes_response = match nodes.json::<serde_json::Value>().await {
-            Ok(json) => {
-                println!("Successfully parsed nodes response: {:?}", json);
-                json
-            },
+            let backoff = policy.backoff(attempt);
+            warn!(?backoff, attempt = attempt + 1, "Request failed, retrying");
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// Awaits `request`, recording it with the inspector when one is attached.
+    async fn recorded(
+        &self,
+        endpoint: &Endpoint<'_>,
+        request: impl Future<Output = Result<Response, Error>>,
+    ) -> Result<Response, Error> {
+        let inspector = match &self.inspector {
+            Some(inspector) => inspector,
+            None => return request.await,
+        };
+
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let started = Instant::now();
+        let result = request.await;
+        let mut record = RequestRecord {
+            id: 0,
+            connection_id: self.connection_info.id.clone(),
+            timestamp,
+            method: format!("{:?}", endpoint.method).to_uppercase(),
+            node: self.active_nodes().first().cloned().unwrap_or_default(),
+            path: endpoint.path.clone(),
+            query: None,
+            body: endpoint.body.map(|body| inspector::redact_body(body, endpoint.redact).to_string()),
+            status: None,
+            response_size: None,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: None,
+        };
+        match &result {
+            Ok(response) => {
+                let url = response.url();
+                record.method = format!("{:?}", response.method()).to_uppercase();
+                record.node = url[..Position::BeforePath].to_string();
+                record.path = url.path().to_string();
+                record.query = url.query().map(String::from);
+                record.status = Some(response.status_code().as_u16());
+                record.response_size = response.content_length();
+            }
+            Err(e) => record.error = Some(e.to_string()),
+        }
+        inspector.record(record);
+        result
+    }
+
+    /// Stops sniffing and tears down the TLS relay and SSH tunnel, if any. Clones of this client stop working afterwards.
+    pub async fn close(&self) {
+        if let Some(sniffer) = &self.sniffer {
+            sniffer.0.abort();
+        }
+        if let Some(relay) = &self.relay {
+            relay.close();
+        }
+        if let Some(tunnel) = &self.tunnel {
+            tunnel.close().await;
+        }
+    }
+
+    /// Rebuilds the client from its config, reopening the transport and any SSH tunnel.
+    pub async fn reconnect(&self) -> AppResult<Self> {
+        Self::connect(self.config.clone(), self.connection_info.clone(), self.inspector.clone()).await
+    }
+
+    /// Single ping without retries, used by the health monitor.
+    pub async fn ping(&self) -> AppResult<()> {
+        let request = self.client
+            .ping()
+            .request_timeout(self.config.timeout())
+            .send();
+        let response = self.recorded(&Endpoint::new(Method::Head, "/"), request)
+            .await
+            .map_err(|e| AppError::ConnectionError(Message::key("connection.health_check_failed").arg("error", e)))?;
+        ensure_success(response).await?;
+        Ok(())
+    }
+
+    pub fn set_active(&self, is_active: bool) {
+        self.active.store(is_active, Ordering::Relaxed);
+    }
+
+    pub fn get_connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            is_active: self.active.load(Ordering::Relaxed),
+            ..self.connection_info.clone()
+        }
+    }
+
+    pub async fn test_connection(&self) -> AppResult<bool> {
+        debug!(hosts = ?self.config.hosts, "Testing connection");
+        match self.send(Endpoint::new(Method::Head, "/"), || async move { self.client.ping().send().await }).await {
+            Ok(_) => Ok(true),
+            Err(AppError::ResponseError(e)) => {
+                warn!(error = %e, "Connection test rejected");
+                Err(AppError::ResponseError(e))
+            }
             Err(e) => {
-                println!("Failed to parse nodes response: {:?}", e);
-                return Err(AppError::ElasticsearchError(e.to_string()));
+                warn!(error = %e, "Connection test failed");
+                Err(AppError::ConnectionError(Message::key("connection.test_failed").arg("error", e.detail())))
             }
-        };
+        }
+    }
+
+    pub fn get_client(&self) -> &Elasticsearch {
+        &self.client
+    }
+
+    pub fn get_config(&self) -> &ElasticsearchConfig {
+        &self.config
+    }
+
+    pub async fn list_indices(&self, opaque_id: Option<&str>) -> AppResult<Vec<IndexInfo>> {
+        let opaque_id = opaque_header(opaque_id)?;
+        let opaque_id = &opaque_id;
+        let response = self
+            .send(Endpoint::new(Method::Get, "/_cat/indices"), || async move {
+                let cat = self.client.cat();
+                let mut request = cat
+                    .indices(CatIndicesParts::None)
+                    .format("json")
+                    .h(&CAT_INDEX_COLUMNS)
+                    .bytes(Bytes::B);
+                if let Some(value) = opaque_id {
+                    request = request.header(HeaderName::from_static(X_OPAQUE_ID), value.clone());
+                }
+                request.send().await
+            })
+            .await?;
+
+        let indices: Vec<serde_json::Value> = response.json()
+            .await?;
+
+        indices.iter()
+            .map(|index| {
+                Ok(IndexInfo {
+                    name: required_str(index, "index")?,
+                    // Closed indices report no document or size counts.
+                    docs_count: cat_number(index, "docs.count"),
+                    size_in_bytes: cat_number(index, "store.size"),
+                    health: cat_health(index),
+                    status: required_str(index, "status")?,
+                    uuid: required_str(index, "uuid")?,
+                    creation_date: required_str(index, "creation.date")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn get_cluster_info(&self) -> AppResult<ClusterInfo> {
+        let health_response = self
+            .send(Endpoint::new(Method::Get, "/_cluster/health"), || async move {
+                self.client
+                    .cluster()
+                    .health(ClusterHealthParts::None)
+                    .send()
+                    .await
+            })
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        trace!(response = %health_response, "Cluster health");
+
+        let nodes_response = self
+            .send(Endpoint::new(Method::Get, "/_nodes"), || async move {
+                self.client
+                    .nodes()
+                    .info(NodesInfoParts::None)
+                    .send()
+                    .await
+            })
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        trace!(response = %nodes_response, "Nodes info");
 
         // Extract node information
-        println!("Extracting node information...");
         let mut node_infos = Vec::new();
         if let Some(nodes) = nodes_response["nodes"].as_object() {
             for (_id, node) in nodes {
                 let node_info = NodeInfo {
-                    name: node["name"].as_str().unwrap_or("unknown").to_string(),
-                    version: node["version"].as_str().unwrap_or("unknown").to_string(),
+                    name: required_str(node, "name")?,
+                    version: required_str(node, "version")?,
                     roles: node["roles"]
                         .as_array()
                         .map(|roles| {
@@ -344,40 +712,36 @@ impl ESClient {
             }
         }
 
-        println!("Successfully extracted node information");
-        println!("Creating final cluster info response");
-
+        let version = self.server_version()?;
         Ok(ClusterInfo {
-            name: nodes_response["cluster_name"].as_str().unwrap_or("unknown").to_string(),
-            status: health_response["status"].as_str().unwrap_or("unknown").to_string(),
+            name: required_str(&health_response, "cluster_name")?,
+            status: required_str(&health_response, "status")?,
             number_of_nodes: health_response["number_of_nodes"].as_i64().unwrap_or(0),
             active_primary_shards: health_response["active_primary_shards"].as_i64().unwrap_or(0),
             active_shards: health_response["active_shards"].as_i64().unwrap_or(0),
             relocating_shards: health_response["relocating_shards"].as_i64().unwrap_or(0),
             initializing_shards: health_response["initializing_shards"].as_i64().unwrap_or(0),
             unassigned_shards: health_response["unassigned_shards"].as_i64().unwrap_or(0),
-            version: nodes_response["nodes"]
-                .as_object()
-                .and_then(|nodes| nodes.values().next())
-                .and_then(|node| node["version"].as_str())
-                .unwrap_or("unknown")
-                .to_string(),
+            version: version.number.clone(),
+            distribution: version.distribution,
             nodes: node_infos,
         })
     }
 
     pub async fn get_shards_info(&self) -> AppResult<Vec<ShardInfo>> {
-        let response = self.client
-            .cat()
-            .shards(elasticsearch::cat::CatShardsParts::None)
-            .format("json")
-            .send()
-            .await
-            .map_err(|e| AppError::ElasticsearchError(e.to_string()))?;
+        let response = self
+            .send(Endpoint::new(Method::Get, "/_cat/shards"), || async move {
+                self.client
+                    .cat()
+                    .shards(elasticsearch::cat::CatShardsParts::None)
+                    .format("json")
+                    .send()
+                    .await
+            })
+            .await?;
 
         let shards: Vec<serde_json::Value> = response.json()
-            .await
-            .map_err(|e| AppError::ElasticsearchError(e.to_string()))?;
+            .await?;
 
         let shard_infos = shards.into_iter()
             .map(|shard| {
@@ -401,11 +765,15 @@ impl ESClient {
         Ok(shard_infos)
     }
 
-    pub async fn list_snapshot_repositories(&self) -> Result<Vec<SnapshotRepository>, Error> {
-        let response = self.client
-            .snapshot()
-            .get_repository(SnapshotGetRepositoryParts::None)
-            .send()
+    pub async fn list_snapshot_repositories(&self) -> AppResult<Vec<SnapshotRepository>> {
+        let response = self
+            .send(Endpoint::new(Method::Get, "/_snapshot"), || async move {
+                self.client
+                    .snapshot()
+                    .get_repository(SnapshotGetRepositoryParts::None)
+                    .send()
+                    .await
+            })
             .await?;
 
         let repositories = response.json::<Value>().await?;
@@ -413,13 +781,11 @@ impl ESClient {
 
         if let Value::Object(repos) = repositories {
             for (name, details) in repos {
-                if let Value::Object(repo_details) = details {
-                    let type_ = repo_details.get("type").and_then(|t| t.as_str()).unwrap_or("unknown").to_string();
-                    let settings = repo_details.get("settings").cloned().unwrap_or(Value::Null);
+                if details.is_object() {
                     result.push(SnapshotRepository {
+                        type_: required_str(&details, "type")?,
+                        settings: details["settings"].clone(),
                         name,
-                        type_,
-                        settings,
                     });
                 }
             }
@@ -428,17 +794,27 @@ impl ESClient {
         Ok(result)
     }
 
-    pub async fn list_snapshots(&self, repository: &str) -> Result<Vec<Snapshot>, Error> {
-        let response = self.client
-            .snapshot()
-            .get(SnapshotGetParts::RepositorySnapshot(repository, &["_all"]))
-            .send()
+    pub async fn list_snapshots(&self, repository: &str) -> AppResult<Vec<Snapshot>> {
+        let response = self
+            .send(Endpoint::new(Method::Get, format!("/_snapshot/{}/_all", repository)), || async move {
+                self.client
+                    .snapshot()
+                    .get(SnapshotGetParts::RepositorySnapshot(repository, &["_all"]))
+                    .send()
+                    .await
+            })
             .await?;
 
-        let snapshots = response.json::<Value>().await?;
+        let snapshots = response.json::<Value>()
+            .await?;
         let mut result = Vec::new();
 
-        if let Some(snapshots_array) = snapshots.get("snapshots").and_then(|s| s.as_array()) {
+        // Elasticsearch 7.4 to 7.12 wraps the list in a `responses` array, one entry per repository.
+        let snapshots_array = snapshots
+            .get("snapshots")
+            .or_else(|| snapshots["responses"].get(0).and_then(|r| r.get("snapshots")))
+            .and_then(|s| s.as_array());
+        if let Some(snapshots_array) = snapshots_array {
             for snapshot in snapshots_array {
                 if let Some(obj) = snapshot.as_object() {
                     let shards = obj.get("shards")
@@ -446,8 +822,8 @@ impl ESClient {
                         .map(|s| s.to_owned())
                         .unwrap_or_default();
                     result.push(Snapshot {
-                        snapshot: obj.get("snapshot").and_then(|s| s.as_str()).unwrap_or("").to_string(),
-                        state: obj.get("state").and_then(|s| s.as_str()).unwrap_or("").to_string(),
+                        snapshot: required_str(snapshot, "snapshot")?,
+                        state: required_str(snapshot, "state")?,
                         start_time: obj.get("start_time").and_then(|s| s.as_str()).unwrap_or("").to_string(),
                         end_time: obj.get("end_time").and_then(|s| s.as_str()).map(|s| s.to_string()),
                         duration_in_millis: obj.get("duration_in_millis").and_then(|d| d.as_i64()),
@@ -472,18 +848,26 @@ impl ESClient {
         name: &str,
         type_: &str,
         settings: Value,
-    ) -> Result<(), Error> {
+    ) -> AppResult<()> {
         let body = json!({
             "type": type_,
             "settings": settings
         });
 
-        self.client
-            .snapshot()
-            .create_repository(SnapshotCreateRepositoryParts::Repository(name))
+        let body = &body;
+        // Repository settings can hold cloud credentials.
+        let endpoint = Endpoint::new(Method::Post, format!("/_snapshot/{}", name))
             .body(body)
-            .send()
-            .await?;
+            .redact(&["settings"]);
+        self.send(endpoint, || async move {
+            self.client
+                .snapshot()
+                .create_repository(SnapshotCreateRepositoryParts::Repository(name))
+                .body(body.clone())
+                .send()
+                .await
+        })
+        .await?;
 
         Ok(())
     }
@@ -493,7 +877,7 @@ impl ESClient {
         repository: &str,
         snapshot: &str,
         indices: Option<Vec<String>>,
-    ) -> Result<(), Error> {
+    ) -> AppResult<()> {
         let mut body = json!({
             "ignore_unavailable": true,
             "include_global_state": false
@@ -503,13 +887,18 @@ impl ESClient {
             body.as_object_mut().unwrap().insert("indices".to_string(), json!(idx));
         }
 
-        self.client
-            .snapshot()
-            .create(SnapshotCreateParts::RepositorySnapshot(repository, snapshot))
-            .body(body)
-            .wait_for_completion(false)
-            .send()
-            .await?;
+        let body = &body;
+        let endpoint = Endpoint::new(Method::Post, format!("/_snapshot/{}/{}", repository, snapshot)).body(body);
+        self.send(endpoint, || async move {
+            self.client
+                .snapshot()
+                .create(SnapshotCreateParts::RepositorySnapshot(repository, snapshot))
+                .body(body.clone())
+                .wait_for_completion(false)
+                .send()
+                .await
+        })
+        .await?;
 
         Ok(())
     }
@@ -518,12 +907,17 @@ impl ESClient {
         &self,
         repository: &str,
         snapshot: &str,
-    ) -> Result<(), Error> {
-        self.client
-            .snapshot()
-            .delete(SnapshotDeleteParts::RepositorySnapshot(repository, &[snapshot]))
-            .send()
-            .await?;
+    ) -> AppResult<()> {
+        let snapshots = [snapshot];
+        let endpoint = Endpoint::new(Method::Delete, format!("/_snapshot/{}/{}", repository, snapshot));
+        self.send(endpoint, || async move {
+            self.client
+                .snapshot()
+                .delete(SnapshotDeleteParts::RepositorySnapshot(repository, &snapshots))
+                .send()
+                .await
+        })
+        .await?;
 
         Ok(())
     }
@@ -533,7 +927,7 @@ impl ESClient {
         repository: &str,
         snapshot: &str,
         indices: Option<Vec<String>>,
-    ) -> Result<(), Error> {
+    ) -> AppResult<()> {
         let mut body = json!({
             "ignore_unavailable": true,
             "include_global_state": false
@@ -543,22 +937,31 @@ impl ESClient {
             body.as_object_mut().unwrap().insert("indices".to_string(), json!(idx));
         }
 
-        self.client
-            .snapshot()
-            .restore(SnapshotRestoreParts::RepositorySnapshot(repository, snapshot))
-            .body(body)
-            .wait_for_completion(false)
-            .send()
-            .await?;
+        let body = &body;
+        let endpoint = Endpoint::new(Method::Post, format!("/_snapshot/{}/{}/_restore", repository, snapshot)).body(body);
+        self.send(endpoint, || async move {
+            self.client
+                .snapshot()
+                .restore(SnapshotRestoreParts::RepositorySnapshot(repository, snapshot))
+                .body(body.clone())
+                .wait_for_completion(false)
+                .send()
+                .await
+        })
+        .await?;
 
         Ok(())
     }
 
-    pub async fn get_cluster_health(&self) -> Result<ClusterHealth, Error> {
-        let response = self.client
-            .cluster()
-            .health(elasticsearch::cluster::ClusterHealthParts::None)
-            .send()
+    pub async fn get_cluster_health(&self) -> AppResult<ClusterHealth> {
+        let response = self
+            .send(Endpoint::new(Method::Get, "/_cluster/health"), || async move {
+                self.client
+                    .cluster()
+                    .health(elasticsearch::cluster::ClusterHealthParts::None)
+                    .send()
+                    .await
+            })
             .await?;
 
         let health = response.json::<Value>().await?;
@@ -581,66 +984,84 @@ impl ESClient {
         })
     }
 
-    pub async fn get_cluster_stats(&self) -> Result<Value, Error> {
-        let response = self.client
-            .cluster()
-            .stats(elasticsearch::cluster::ClusterStatsParts::None)
-            .send()
+    pub async fn get_cluster_stats(&self, timeout: Option<Duration>, opaque_id: Option<&str>) -> AppResult<Value> {
+        let opaque_id = opaque_header(opaque_id)?;
+        let opaque_id = &opaque_id;
+        let response = self
+            .send(Endpoint::new(Method::Get, "/_cluster/stats"), || async move {
+                let cluster = self.client.cluster();
+                let mut request = cluster.stats(elasticsearch::cluster::ClusterStatsParts::None);
+                if let Some(timeout) = timeout {
+                    request = request.request_timeout(timeout);
+                }
+                if let Some(value) = opaque_id {
+                    request = request.header(HeaderName::from_static(X_OPAQUE_ID), value.clone());
+                }
+                request.send().await
+            })
             .await?;
 
-        response.json::<Value>().await.map_err(Error::from)
+        Ok(response.json::<Value>().await?)
     }
 
-    pub async fn get_index_details(&self, index_name: &str) -> Result<IndexDetails, Error> {
+    pub async fn get_index_details(&self, index_name: &str) -> AppResult<IndexDetails> {
+        let version = self.server_version()?;
+
         // 获取索引健康状态
-        let health = self.client
-            .cat()
-            .indices(CatIndicesParts::Index(&[index_name]))
-            .format("json")
-            .send()
+        let indices = [index_name];
+        let health = self
+            .send(Endpoint::new(Method::Get, format!("/_cat/indices/{}", index_name)), || async move {
+                self.client
+                    .cat()
+                    .indices(CatIndicesParts::Index(&indices))
+                    .format("json")
+                    .h(&CAT_INDEX_COLUMNS)
+                    .bytes(Bytes::B)
+                    .send()
+                    .await
+            })
             .await?
             .json::<Vec<Value>>()
             .await?
             .first()
             .cloned()
-            .ok_or_else(|| Error::from(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Index {} not found", index_name)
-            )))?;
+            .ok_or_else(|| AppError::ElasticsearchError(Message::key("index.not_found").arg("index", index_name)))?;
 
         // 获取索引设置和映射
-        let index_info = self.client
-            .indices()
-            .get(IndicesGetParts::Index(&[index_name]))
-            .send()
+        let index_info = self
+            .send(Endpoint::new(Method::Get, format!("/{}", index_name)), || async move {
+                self.client
+                    .indices()
+                    .get(IndicesGetParts::Index(&indices))
+                    .send()
+                    .await
+            })
             .await?
             .json::<Value>()
             .await?;
 
-        let index_settings = index_info[index_name]["settings"].clone();
-        let index_mappings = index_info[index_name]["mappings"].clone();
+        // Aliases resolve to the concrete index, which is the key of the response.
+        let concrete = required_str(&health, "index")?;
+        let index = index_info.get(&concrete)
+            .ok_or_else(|| AppError::ElasticsearchError(Message::key("index.not_found").arg("index", index_name)))?;
+        let index_settings = index["settings"].clone();
+        let index_mappings = version.normalize_mappings(index["mappings"].clone());
 
         // 解析健康状态信息
-        let health_str = health["health"].as_str().unwrap_or("unknown").to_string();
-        let status = health["status"].as_str().unwrap_or("unknown").to_string();
-        let uuid = health["uuid"].as_str().unwrap_or("unknown").to_string();
-        let docs_count = health["docs.count"].as_str().unwrap_or("0").parse::<i64>().unwrap_or(0);
-        let docs_deleted = health["docs.deleted"].as_str().unwrap_or("0").parse::<i64>().unwrap_or(0);
-        let size_in_bytes = health["store.size"].as_str()
-            .unwrap_or("0")
-            .replace("b", "")
-            .parse::<i64>()
-            .unwrap_or(0);
-        let creation_date = index_settings["index"]["creation_date"]
-            .as_str()
-            .unwrap_or("0")
+        let health_str = cat_health(&health);
+        let status = required_str(&health, "status")?;
+        let uuid = required_str(&health, "uuid")?;
+        let docs_count = cat_number(&health, "docs.count");
+        let docs_deleted = cat_number(&health, "docs.deleted");
+        let size_in_bytes = cat_number(&health, "store.size");
+        let creation_date = required_str(&health, "creation.date")?
             .parse::<i64>()
-            .unwrap_or(0);
-        let primary_shards = health["pri"].as_str().unwrap_or("0").parse::<i64>().unwrap_or(0);
-        let replica_shards = health["rep"].as_str().unwrap_or("0").parse::<i64>().unwrap_or(0);
+            .map_err(|e| AppError::ElasticsearchError(Message::key("index.invalid_creation_date").arg("error", e)))?;
+        let primary_shards = cat_number(&health, "pri");
+        let replica_shards = cat_number(&health, "rep");
 
         Ok(IndexDetails {
-            name: index_name.to_string(),
+            name: concrete,
             health: health_str,
             status,
             uuid,
@@ -654,4 +1075,244 @@ impl ESClient {
             mappings: index_mappings,
         })
     }
-} 
\ No newline at end of file
+
+    /// Cancels the server-side tasks started with `X-Opaque-Id: opaque_id` and returns
+    /// how many were cancelled. Child tasks are cancelled along with their parent.
+    pub async fn cancel_tasks(&self, opaque_id: &str) -> AppResult<usize> {
+        let request = self.client.send(
+            Method::Get,
+            "/_tasks",
+            HeaderMap::new(),
+            Some(&[("group_by", "none"), ("detailed", "false")]),
+            None::<()>,
+            Some(self.config.timeout()),
+        );
+        let response = self.recorded(&Endpoint::new(Method::Get, "/_tasks"), request).await?;
+        let tasks = ensure_success(response).await?.json::<Value>().await?;
+
+        let task_ids: Vec<String> = tasks["tasks"]
+            .as_array()
+            .map(|tasks| {
+                tasks.iter()
+                    .filter(|task| task["parent_task_id"].is_null())
+                    .filter(|task| task["cancellable"].as_bool().unwrap_or(false))
+                    .filter(|task| {
+                        task["headers"]
+                            .as_object()
+                            .and_then(|headers| headers.iter().find(|(name, _)| name.eq_ignore_ascii_case(X_OPAQUE_ID)))
+                            .and_then(|(_, value)| value.as_str())
+                            == Some(opaque_id)
+                    })
+                    .filter_map(|task| Some(format!("{}:{}", task["node"].as_str()?, task["id"].as_i64()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut cancelled = 0;
+        for task_id in task_ids {
+            let path = format!("/_tasks/{}/_cancel", task_id);
+            let request = self.client
+                .send(Method::Post, &path, HeaderMap::new(), None::<&()>, None::<()>, Some(self.config.timeout()));
+            let response = self.recorded(&Endpoint::new(Method::Post, path.as_str()), request).await?;
+            if response.status_code().is_success() {
+                cancelled += 1;
+            } else {
+                warn!(task_id = %task_id, status = %response.status_code(), "Failed to cancel task");
+            }
+        }
+
+        Ok(cancelled)
+    }
+
+    pub async fn search(
+        &self,
+        index: &str,
+        body: Value,
+        timeout: Option<Duration>,
+        opaque_id: Option<&str>,
+    ) -> AppResult<Value> {
+        let indices = [index];
+        let path = format!("/{}/_search", index);
+        self.run_search(SearchParts::Index(&indices), path, body, None, timeout, opaque_id).await
+    }
+
+    /// Searches the point in time named by the `pit` of `body`, which takes the place
+    /// of an index.
+    pub async fn search_point_in_time(
+        &self,
+        body: Value,
+        timeout: Option<Duration>,
+        opaque_id: Option<&str>,
+    ) -> AppResult<Value> {
+        self.run_search(SearchParts::None, "/_search".to_string(), body, None, timeout, opaque_id).await
+    }
+
+    /// Starts a scroll over `index`, kept for `keep_alive` between batches. Used where
+    /// point in time is unavailable.
+    pub async fn search_scroll(&self, index: &str, body: Value, keep_alive: &str) -> AppResult<Value> {
+        let indices = [index];
+        let path = format!("/{}/_search", index);
+        self.run_search(SearchParts::Index(&indices), path, body, Some(keep_alive), None, None).await
+    }
+
+    /// Next batch of the scroll `scroll_id`.
+    pub async fn scroll(&self, scroll_id: &str, keep_alive: &str) -> AppResult<Value> {
+        let body = json!({ "scroll": keep_alive, "scroll_id": scroll_id });
+        let body = &body;
+        let endpoint = Endpoint::new(Method::Post, "/_search/scroll").body(body);
+        let response = self
+            .send(endpoint, || async move {
+                self.client
+                    .scroll(ScrollParts::None)
+                    .body(body.clone())
+                    .send()
+                    .await
+            })
+            .await?;
+        Ok(response.json::<Value>().await?)
+    }
+
+    pub async fn clear_scroll(&self, scroll_id: &str) -> AppResult<()> {
+        let body = json!({ "scroll_id": [scroll_id] });
+        let body = &body;
+        let endpoint = Endpoint::new(Method::Delete, "/_search/scroll").body(body);
+        self.send(endpoint, || async move {
+            self.client
+                .clear_scroll(ClearScrollParts::None)
+                .body(body.clone())
+                .send()
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn run_search(
+        &self,
+        parts: SearchParts<'_>,
+        path: String,
+        body: Value,
+        scroll: Option<&str>,
+        timeout: Option<Duration>,
+        opaque_id: Option<&str>,
+    ) -> AppResult<Value> {
+        let parts = &parts;
+        let body = &body;
+        let opaque_id = opaque_header(opaque_id)?;
+        let opaque_id = &opaque_id;
+        let mut endpoint = Endpoint::new(Method::Post, path).body(body);
+        // Repeating the request that starts a scroll would leave the first context open.
+        if scroll.is_none() {
+            endpoint = endpoint.read();
+        }
+        let response = self
+            .send(endpoint, || async move {
+                let mut request = self.client
+                    .search(parts.clone())
+                    .body(body.clone());
+                if let Some(scroll) = scroll {
+                    request = request.scroll(scroll);
+                }
+                if let Some(timeout) = timeout {
+                    request = request.request_timeout(timeout);
+                }
+                if let Some(value) = opaque_id {
+                    request = request.header(HeaderName::from_static(X_OPAQUE_ID), value.clone());
+                }
+                request.send().await
+            })
+            .await?;
+
+        Ok(response.json::<Value>().await?)
+    }
+
+    /// Opens a point in time on `index`, kept for `keep_alive` (e.g. `5m`) after each
+    /// use, and returns its id.
+    pub async fn open_point_in_time(&self, index: &str, keep_alive: &str) -> AppResult<String> {
+        let version = self.server_version()?;
+        version.require(Feature::PointInTime)?;
+        let (path, id_field) = version.open_point_in_time_path(index);
+        let path = path.as_str();
+        let response = self
+            .send(Endpoint::new(Method::Post, path), || async move {
+                self.client
+                    .send(
+                        Method::Post,
+                        path,
+                        HeaderMap::new(),
+                        Some(&[("keep_alive", keep_alive)]),
+                        None::<()>,
+                        Some(self.config.timeout()),
+                    )
+                    .await
+            })
+            .await?
+            .json::<Value>()
+            .await?;
+        required_str(&response, id_field)
+    }
+
+    pub async fn close_point_in_time(&self, pit_id: &str) -> AppResult<()> {
+        let (path, body) = self.server_version()?.close_point_in_time_request(pit_id);
+        let endpoint = Endpoint::new(Method::Delete, path).body(&body);
+        let body = &body;
+        self.send(endpoint, || async move {
+            self.client
+                .send(
+                    Method::Delete,
+                    path,
+                    HeaderMap::new(),
+                    None::<&()>,
+                    Some(JsonBody::new(body.clone())),
+                    Some(self.config.timeout()),
+                )
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+/// Columns requested from `_cat/indices`; `creation.date` is not part of the default set.
+const CAT_INDEX_COLUMNS: [&str; 10] = [
+    "health", "status", "index", "uuid", "pri", "rep",
+    "docs.count", "docs.deleted", "store.size", "creation.date",
+];
+
+fn required_str(value: &Value, field: &str) -> AppResult<String> {
+    value[field]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| AppError::ElasticsearchError(Message::key("response.missing_field").arg("field", field)))
+}
+
+/// `_cat` APIs return numbers as strings and omit them for closed indices.
+fn cat_number(value: &Value, field: &str) -> i64 {
+    value[field].as_str().and_then(|s| s.parse::<i64>().ok()).unwrap_or(0)
+}
+
+/// Elasticsearch 6.x reports no health for closed indices.
+fn cat_health(value: &Value) -> String {
+    match value["health"].as_str() {
+        Some(health) if !health.is_empty() => health.to_string(),
+        _ => "closed".to_string(),
+    }
+}
+
+/// Turns a non-2xx response into [`AppError::ResponseError`] with its decoded body.
+pub(crate) async fn ensure_success(response: Response) -> AppResult<Response> {
+    if response.status_code().is_success() {
+        Ok(response)
+    } else {
+        Err(ResponseError::from_response(response).await.into())
+    }
+}
+
+fn opaque_header(opaque_id: Option<&str>) -> AppResult<Option<HeaderValue>> {
+    opaque_id
+        .map(|id| {
+            HeaderValue::from_str(id)
+                .map_err(|_| AppError::ValidationError(Message::key("request.invalid_id").arg("id", id)))
+        })
+        .transpose()
+}
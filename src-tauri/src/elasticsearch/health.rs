@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::{
+    sync::Mutex,
+    task::JoinHandle,
+    time::MissedTickBehavior,
+};
+use tracing::warn;
+use crate::{
+    config::HealthCheckConfig,
+    ClientMap,
+};
+
+/// Event emitted whenever a connection changes state.
+pub const HEALTH_EVENT: &str = "connection-health";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Down,
+    Reconnecting,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConnectionHealth {
+    pub connection_id: String,
+    pub status: HealthStatus,
+    pub latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// Unix timestamp of the last ping.
+    pub last_checked: i64,
+}
+
+/// Background ping loops, one per connected client.
+#[derive(Default)]
+pub struct HealthMonitor {
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+    statuses: Arc<Mutex<HashMap<String, ConnectionHealth>>>,
+}
+
+impl HealthMonitor {
+    /// Starts (or restarts) monitoring `connection_id`. The loop ends on its own once
+    /// the client is removed from `clients`.
+    pub async fn start(&self, app: AppHandle, clients: ClientMap, connection_id: String) {
+        self.stop(&connection_id).await;
+
+        let config = match clients.read().await.get(&connection_id) {
+            Some(client) => client.get_config().health_check.clone(),
+            None => return,
+        };
+        if !config.enabled {
+            return;
+        }
+
+        let statuses = self.statuses.clone();
+        let id = connection_id.clone();
+        let task = tokio::spawn(async move {
+            monitor(app, clients, statuses, id, config).await;
+        });
+        self.tasks.lock().await.insert(connection_id, task);
+    }
+
+    pub async fn stop(&self, connection_id: &str) {
+        if let Some(task) = self.tasks.lock().await.remove(connection_id) {
+            task.abort();
+        }
+        self.statuses.lock().await.remove(connection_id);
+    }
+
+    pub async fn status(&self, connection_id: &str) -> Option<ConnectionHealth> {
+        self.statuses.lock().await.get(connection_id).cloned()
+    }
+}
+
+async fn monitor(
+    app: AppHandle,
+    clients: ClientMap,
+    statuses: Arc<Mutex<HashMap<String, ConnectionHealth>>>,
+    connection_id: String,
+    config: HealthCheckConfig,
+) {
+    let mut ticker = tokio::time::interval(config.interval());
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately and the client was just pinged on connect.
+    ticker.tick().await;
+
+    let mut status = HealthStatus::Up;
+    let mut failures = 0;
+    loop {
+        ticker.tick().await;
+        let client = match clients.read().await.get(&connection_id) {
+            Some(client) => client.clone(),
+            None => break,
+        };
+
+        let started = Instant::now();
+        let mut health = match client.ping().await {
+            Ok(()) => {
+                failures = 0;
+                ConnectionHealth {
+                    connection_id: connection_id.clone(),
+                    status: HealthStatus::Up,
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    consecutive_failures: 0,
+                    last_error: None,
+                    last_checked: chrono::Utc::now().timestamp(),
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                ConnectionHealth {
+                    connection_id: connection_id.clone(),
+                    status: HealthStatus::Down,
+                    latency_ms: None,
+                    consecutive_failures: failures,
+                    last_error: Some(e.to_string()),
+                    last_checked: chrono::Utc::now().timestamp(),
+                }
+            }
+        };
+
+        if health.status == HealthStatus::Down
+            && config.reconnect_after_failures > 0
+            && failures >= config.reconnect_after_failures
+        {
+            warn!(connection_id = %connection_id, failures, "Health check failed, reconnecting");
+            health.status = HealthStatus::Reconnecting;
+            publish(&app, &clients, &statuses, &health).await;
+
+            match client.reconnect().await {
+                Ok(fresh) => {
+                    let mut map = clients.write().await;
+                    if !map.get(&connection_id).is_some_and(|current| Arc::ptr_eq(current, &client)) {
+                        // Disconnected, or connected anew, while we were reconnecting.
+                        drop(map);
+                        fresh.close().await;
+                        break;
+                    }
+                    map.insert(connection_id.clone(), Arc::new(fresh));
+                    drop(map);
+                    client.close().await;
+
+                    failures = 0;
+                    health.status = HealthStatus::Up;
+                    health.consecutive_failures = 0;
+                    health.last_error = None;
+                }
+                Err(e) => {
+                    warn!(connection_id = %connection_id, error = %e, "Reconnect failed");
+                    // Wait for another full round of failures before the next attempt.
+                    failures = 0;
+                    health.status = HealthStatus::Down;
+                    health.last_error = Some(e.to_string());
+                }
+            }
+            publish(&app, &clients, &statuses, &health).await;
+            status = health.status;
+            continue;
+        }
+
+        if health.status != status {
+            publish(&app, &clients, &statuses, &health).await;
+            status = health.status;
+        } else {
+            statuses.lock().await.insert(connection_id.clone(), health);
+        }
+    }
+}
+
+/// Records `health`, mirrors it into `ConnectionInfo::is_active` and notifies the frontend.
+async fn publish(
+    app: &AppHandle,
+    clients: &ClientMap,
+    statuses: &Mutex<HashMap<String, ConnectionHealth>>,
+    health: &ConnectionHealth,
+) {
+    if let Some(client) = clients.read().await.get(&health.connection_id) {
+        client.set_active(health.status == HealthStatus::Up);
+    }
+    statuses.lock().await.insert(health.connection_id.clone(), health.clone());
+    if let Err(e) = app.emit(HEALTH_EVENT, health.clone()) {
+        warn!(event = HEALTH_EVENT, error = %e, "Failed to emit event");
+    }
+}
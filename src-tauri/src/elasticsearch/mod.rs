@@ -1 +1,7 @@
-pub mod client; 
\ No newline at end of file
+pub mod client;
+pub mod health;
+pub mod pool;
+pub mod proxy;
+pub mod ssh;
+pub mod tls;
+pub mod version;
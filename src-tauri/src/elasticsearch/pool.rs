@@ -0,0 +1,163 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc, RwLock,
+};
+use std::time::Duration;
+use elasticsearch::{
+    http::transport::{Connection, ConnectionPool},
+    nodes::NodesInfoParts,
+    Elasticsearch,
+};
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use url::Url;
+use tracing::{debug, warn};
+use crate::{
+    config::{NodeSelection, PoolConfig},
+    elasticsearch::client::ensure_success,
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// Connection pool whose node list can be replaced by sniffing.
+#[derive(Debug, Clone)]
+pub struct NodePool {
+    connections: Arc<RwLock<Vec<Connection>>>,
+    selection: NodeSelection,
+    cursor: Arc<AtomicUsize>,
+    sniffing: Arc<AtomicBool>,
+}
+
+impl NodePool {
+    pub fn new(urls: Vec<Url>, selection: NodeSelection) -> Self {
+        Self {
+            connections: Arc::new(RwLock::new(urls.into_iter().map(Connection::new).collect())),
+            selection,
+            cursor: Arc::new(AtomicUsize::new(0)),
+            sniffing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Moves a sticky pool on to the next node. Round-robin pools rotate anyway.
+    pub fn mark_failed(&self) {
+        if self.selection == NodeSelection::Sticky {
+            self.cursor.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Replaces the node list. An empty result is ignored so the pool never runs dry.
+    pub fn reseed(&self, urls: Vec<Url>) {
+        if urls.is_empty() {
+            return;
+        }
+        let mut connections = self.connections.write().expect("lock poisoned");
+        *connections = urls.into_iter().map(Connection::new).collect();
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.connections
+            .read()
+            .expect("lock poisoned")
+            .iter()
+            .map(|connection| connection.url().to_string())
+            .collect()
+    }
+}
+
+impl ConnectionPool for NodePool {
+    fn next(&self) -> Connection {
+        let connections = self.connections.read().expect("lock poisoned");
+        let index = match self.selection {
+            NodeSelection::RoundRobin => self.cursor.fetch_add(1, Ordering::Relaxed),
+            NodeSelection::Sticky => self.cursor.load(Ordering::Relaxed),
+        };
+        connections[index % connections.len()].clone()
+    }
+}
+
+/// Asks the cluster for its HTTP-enabled nodes and reseeds `pool` with the ones
+/// `config` accepts. Returns the number of nodes kept.
+pub async fn sniff(
+    client: &Elasticsearch,
+    pool: &NodePool,
+    config: &PoolConfig,
+    scheme: &str,
+    timeout: Duration,
+) -> AppResult<usize> {
+    if pool.sniffing.swap(true, Ordering::Acquire) {
+        return Ok(0);
+    }
+    let result = discover(client, config, scheme, timeout).await;
+    pool.sniffing.store(false, Ordering::Release);
+
+    let urls = result?;
+    let count = urls.len();
+    if count == 0 {
+        warn!("Sniffing found no matching nodes, keeping the current node list");
+    } else {
+        debug!(count, nodes = ?urls, "Sniffed nodes");
+        pool.reseed(urls);
+    }
+    Ok(count)
+}
+
+async fn discover(client: &Elasticsearch, config: &PoolConfig, scheme: &str, timeout: Duration) -> AppResult<Vec<Url>> {
+    let metrics = ["http"];
+    let response = client
+        .nodes()
+        .info(NodesInfoParts::Metric(&metrics))
+        .filter_path(&["nodes.*.roles", "nodes.*.http.publish_address"])
+        .request_timeout(timeout)
+        .send()
+        .await
+        .map_err(|e| AppError::ElasticsearchError(Message::key("pool.sniff_failed").arg("error", e)))?;
+    let response = ensure_success(response).await?;
+
+    let body: Value = response.json()
+        .await
+        .map_err(|e| AppError::ElasticsearchError(Message::key("pool.sniff_failed").arg("error", e)))?;
+    let nodes = match body["nodes"].as_object() {
+        Some(nodes) => nodes,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut urls = Vec::new();
+    for node in nodes.values() {
+        let roles: Vec<String> = node["roles"]
+            .as_array()
+            .map(|roles| roles.iter().filter_map(|r| r.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        if !config.accepts(&roles) {
+            continue;
+        }
+        if let Some(address) = node["http"]["publish_address"].as_str() {
+            urls.push(parse_publish_address(address, scheme)?);
+        }
+    }
+    Ok(urls)
+}
+
+/// Turns `hostname/ip:port` or `ip:port` into a URL, preferring the hostname so
+/// certificate verification keeps working.
+pub fn parse_publish_address(address: &str, scheme: &str) -> AppResult<Url> {
+    let authority = match address.split_once('/') {
+        Some((host, ip_port)) if !host.is_empty() => {
+            let port = ip_port.rsplit_once(':').map(|(_, port)| port).unwrap_or("9200");
+            format!("{}:{}", host, port)
+        }
+        Some((_, ip_port)) => ip_port.to_string(),
+        None => address.to_string(),
+    };
+    Url::parse(&format!("{}://{}", scheme, authority))
+        .map_err(|e| AppError::ElasticsearchError(Message::key("pool.invalid_address").arg("address", address).arg("error", e)))
+}
+
+/// Aborts the periodic sniff task once the last client clone is gone.
+#[derive(Debug)]
+pub struct SnifferGuard(pub JoinHandle<()>);
+
+impl Drop for SnifferGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
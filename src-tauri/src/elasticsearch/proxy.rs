@@ -0,0 +1,37 @@
+use elasticsearch::http::transport::TransportBuilder;
+use url::Url;
+use crate::{
+    config::{non_empty, ElasticsearchConfig},
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// Routes the transport through the configured HTTP CONNECT or SOCKS5 proxy.
+/// The proxy is bypassed entirely when every node matches the no-proxy list.
+pub fn configure(builder: TransportBuilder, config: &ElasticsearchConfig, nodes: &[Url]) -> AppResult<TransportBuilder> {
+    let proxy = match &config.proxy {
+        Some(proxy) => proxy,
+        None => return Ok(builder),
+    };
+
+    if !nodes.is_empty() && nodes.iter().all(|url| url.host_str().map(|host| proxy.bypasses(host)).unwrap_or(false)) {
+        return Ok(builder.disable_proxy());
+    }
+
+    let mut url = Url::parse(&proxy.url)
+        .map_err(|e| AppError::ValidationError(Message::key("config.invalid_proxy_url").arg("error", e)))?;
+    let username = non_empty(&proxy.username);
+    let password = proxy.password.as_deref();
+
+    if proxy.is_socks() {
+        // reqwest only picks up SOCKS credentials from the proxy URL itself.
+        if let Some(username) = username {
+            url.set_username(username)
+                .and_then(|_| url.set_password(password))
+                .map_err(|_| AppError::ValidationError(Message::key("config.invalid_proxy_credentials")))?;
+        }
+        Ok(builder.proxy(url, None, None))
+    } else {
+        Ok(builder.proxy(url, username, password))
+    }
+}
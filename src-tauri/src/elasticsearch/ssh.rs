@@ -0,0 +1,177 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use russh::{
+    client::{self, Handle},
+    keys::{self, PrivateKeyWithHashAlg, PublicKey},
+    Disconnect,
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+use url::Url;
+use tracing::{error, warn};
+use crate::{
+    config::{non_empty, SshTunnelConfig},
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+struct TunnelHandler {
+    host: String,
+    port: u16,
+    accept_unknown_host_key: bool,
+}
+
+impl client::Handler for TunnelHandler {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, server_public_key: &PublicKey) -> Result<bool, Self::Error> {
+        // A changed key is reported as an error by check_known_hosts and always rejected.
+        match keys::check_known_hosts(&self.host, self.port, server_public_key)? {
+            true => Ok(true),
+            false => Ok(self.accept_unknown_host_key),
+        }
+    }
+}
+
+/// An SSH session forwarding local ports to Elasticsearch nodes behind a bastion host.
+pub struct SshTunnel {
+    session: Arc<Handle<TunnelHandler>>,
+    forwards: Vec<JoinHandle<()>>,
+}
+
+impl SshTunnel {
+    /// Opens the session and one local forward per node, returning the tunnel and the
+    /// node URLs rewritten to point at the local ends.
+    pub async fn open(config: &SshTunnelConfig, nodes: &[Url], timeout: Duration) -> AppResult<(Self, Vec<String>)> {
+        let session = tokio::time::timeout(timeout, Self::connect(config))
+            .await
+            .map_err(|_| AppError::ConnectionError(Message::key("ssh.timeout").arg("host", &config.host).arg("port", config.port)))??;
+        let session = Arc::new(session);
+
+        let mut tunnel = SshTunnel { session, forwards: Vec::new() };
+        match tunnel.forward_nodes(nodes).await {
+            Ok(local_urls) => Ok((tunnel, local_urls)),
+            Err(e) => {
+                tunnel.close().await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Forwards a local port to each of `nodes` and returns their local URLs.
+    async fn forward_nodes(&mut self, nodes: &[Url]) -> AppResult<Vec<String>> {
+        let mut local_urls = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let host = node.host_str()
+                .ok_or_else(|| AppError::ValidationError(Message::key("config.missing_host").arg("url", node)))?
+                .to_string();
+            let port = node.port_or_known_default().unwrap_or(9200);
+            let local = self.forward(host, port).await?;
+
+            let mut local_url = node.clone();
+            local_url.set_host(Some(&local.ip().to_string()))
+                .and_then(|_| local_url.set_port(Some(local.port())).map_err(|_| url::ParseError::InvalidPort))
+                .map_err(|e| AppError::ValidationError(Message::key("config.invalid_host_url").arg("error", e)))?;
+            local_urls.push(local_url.to_string());
+        }
+        Ok(local_urls)
+    }
+
+    async fn connect(config: &SshTunnelConfig) -> AppResult<Handle<TunnelHandler>> {
+        let ssh_config = Arc::new(client::Config {
+            keepalive_interval: Some(Duration::from_secs(30)),
+            keepalive_max: 3,
+            ..Default::default()
+        });
+        let handler = TunnelHandler {
+            host: config.host.clone(),
+            port: config.port,
+            accept_unknown_host_key: config.accept_unknown_host_key,
+        };
+
+        let mut session = client::connect(ssh_config, (config.host.as_str(), config.port), handler)
+            .await
+            .map_err(|e| AppError::ConnectionError(Message::key("ssh.unreachable").arg("host", &config.host).arg("port", config.port).arg("error", e)))?;
+
+        let auth = if let Some(key_path) = non_empty(&config.key_path) {
+            let key = keys::load_secret_key(key_path, non_empty(&config.key_passphrase))
+                .map_err(|e| AppError::ValidationError(Message::key("ssh.key_failed").arg("path", key_path).arg("error", e)))?;
+            let hash_alg = session.best_supported_rsa_hash()
+                .await
+                .map_err(|e| AppError::ConnectionError(Message::key("ssh.negotiation_failed").arg("error", e)))?
+                .flatten();
+            session
+                .authenticate_publickey(&config.user, PrivateKeyWithHashAlg::new(Arc::new(key), hash_alg))
+                .await
+        } else if let Some(password) = non_empty(&config.password) {
+            session.authenticate_password(&config.user, password).await
+        } else {
+            return Err(AppError::ValidationError(Message::key("config.ssh_credentials_required")));
+        }
+        .map_err(|e| AppError::ConnectionError(Message::key("ssh.auth_failed").arg("error", e)))?;
+
+        if !auth.success() {
+            return Err(AppError::ConnectionError(Message::key("ssh.user_rejected").arg("user", &config.user)));
+        }
+
+        Ok(session)
+    }
+
+    /// Listens on an ephemeral loopback port and pipes every accepted connection
+    /// through a `direct-tcpip` channel to `host:port`.
+    async fn forward(&mut self, host: String, port: u16) -> AppResult<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| AppError::ConnectionError(Message::key("ssh.listen_failed").arg("error", e)))?;
+        let local = listener
+            .local_addr()
+            .map_err(|e| AppError::ConnectionError(Message::key("ssh.listen_failed").arg("error", e)))?;
+
+        let session = self.session.clone();
+        self.forwards.push(tokio::spawn(async move {
+            loop {
+                let (mut socket, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        error!(error = %e, "SSH tunnel listener failed");
+                        break;
+                    }
+                };
+
+                let session = session.clone();
+                let host = host.clone();
+                tokio::spawn(async move {
+                    let channel = session
+                        .channel_open_direct_tcpip(host.as_str(), port as u32, peer.ip().to_string(), peer.port() as u32)
+                        .await;
+                    match channel {
+                        Ok(channel) => {
+                            let mut stream = channel.into_stream();
+                            let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                        }
+                        Err(e) => warn!(host = %host, port, error = %e, "SSH tunnel connection failed"),
+                    }
+                });
+            }
+        }));
+
+        Ok(local)
+    }
+
+    pub async fn close(&self) {
+        for forward in &self.forwards {
+            forward.abort();
+        }
+        let _ = self.session
+            .disconnect(Disconnect::ByApplication, "connection closed", "en")
+            .await;
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        for forward in &self.forwards {
+            forward.abort();
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use elasticsearch::{
+    auth::{ClientCertificate, Credentials},
+    cert::{Certificate, CertificateValidation},
+    http::transport::TransportBuilder,
+};
+use p12_keystore::KeyStore;
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        verify_server_cert_signed_by_trust_anchor, verify_server_name,
+    },
+    crypto::{self, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    server::ParsedCertificate,
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tokio_rustls::{client::TlsStream, TlsConnector};
+use url::Url;
+use tracing::{error, warn};
+use crate::{
+    config::{non_empty, normalize_fingerprint, ElasticsearchConfig, VerificationMode},
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// Applies the CA bundle, verification mode and client certificate from the config.
+pub fn configure(mut builder: TransportBuilder, config: &ElasticsearchConfig) -> AppResult<TransportBuilder> {
+    let ca = match non_empty(&config.ca_cert_path) {
+        Some(path) => {
+            let pem = std::fs::read(path)
+                .map_err(|e| AppError::ValidationError(Message::key("tls.ca_read_failed").arg("path", path).arg("error", e)))?;
+            let cert = Certificate::from_pem(&pem)
+                .map_err(|e| AppError::ValidationError(Message::key("tls.ca_invalid").arg("path", path).arg("error", e)))?;
+            Some(cert)
+        }
+        None => None,
+    };
+
+    let validation = match (config.verification_mode, ca) {
+        (VerificationMode::Full, Some(cert)) => Some(CertificateValidation::Full(cert)),
+        (VerificationMode::Full, None) => None,
+        (VerificationMode::Certificate, Some(cert)) => Some(CertificateValidation::Certificate(cert)),
+        (VerificationMode::Certificate, None) => {
+            return Err(AppError::ValidationError(
+                Message::key("config.ca_required")
+            ));
+        }
+        (VerificationMode::None, _) => Some(CertificateValidation::None),
+    };
+    if let Some(validation) = validation {
+        builder = builder.cert_validation(validation);
+    }
+
+    if let Some(path) = non_empty(&config.client_cert_path) {
+        let archive = std::fs::read(path)
+            .map_err(|e| AppError::ValidationError(Message::key("tls.client_cert_read_failed").arg("path", path).arg("error", e)))?;
+        let password = non_empty(&config.client_cert_password).map(str::to_string);
+        builder = builder.auth(Credentials::Certificate(ClientCertificate::Pkcs12(archive, password)));
+    }
+
+    Ok(builder)
+}
+
+/// Local plain http endpoints that open the TLS connection to each node themselves.
+/// The transport takes no custom verifier and trusts any CA it is given next to the
+/// system store, so a pinned fingerprint is enforced here instead, on every handshake.
+pub struct PinnedRelay {
+    forwards: Vec<JoinHandle<()>>,
+}
+
+impl PinnedRelay {
+    /// Checks the pin against every node once and listens on a loopback port per node,
+    /// returning the relay and the node URLs rewritten to point at it. `nodes` pairs the
+    /// configured URL, whose host the certificate is issued for, with the URL to dial,
+    /// which differs behind an SSH tunnel.
+    pub async fn open(config: &ElasticsearchConfig, nodes: &[(Url, Url)]) -> AppResult<(Self, Vec<String>)> {
+        let fingerprint = non_empty(&config.ca_fingerprint)
+            .map(normalize_fingerprint)
+            .unwrap_or_default();
+        let connector = TlsConnector::from(client_config(config, fingerprint)?);
+        let timeout = config.timeout();
+
+        let mut relay = PinnedRelay { forwards: Vec::new() };
+        let mut local_urls = Vec::with_capacity(nodes.len());
+        for (node, dial) in nodes {
+            let target = Target::new(node, dial)?;
+            // Fail the connect with a clear error rather than on the first request.
+            target.handshake(&connector, timeout).await.map_err(|e| handshake_error(e, node))?;
+            let local = relay.forward(connector.clone(), target, timeout).await?;
+
+            let mut local_url = node.clone();
+            local_url.set_scheme("http")
+                .map_err(|_| url::ParseError::InvalidDomainCharacter)
+                .and_then(|_| local_url.set_host(Some(&local.ip().to_string())))
+                .and_then(|_| local_url.set_port(Some(local.port())).map_err(|_| url::ParseError::InvalidPort))
+                .map_err(|e| AppError::ValidationError(Message::key("config.invalid_host_url").arg("error", e)))?;
+            local_urls.push(local_url.to_string());
+        }
+        Ok((relay, local_urls))
+    }
+
+    /// Listens on an ephemeral loopback port and pipes every accepted connection
+    /// through a new, pin-checked TLS connection to `target`.
+    async fn forward(&mut self, connector: TlsConnector, target: Target, timeout: Duration) -> AppResult<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| AppError::ConnectionError(Message::key("tls.listen_failed").arg("error", e)))?;
+        let local = listener
+            .local_addr()
+            .map_err(|e| AppError::ConnectionError(Message::key("tls.listen_failed").arg("error", e)))?;
+
+        let target = Arc::new(target);
+        self.forwards.push(tokio::spawn(async move {
+            loop {
+                let mut socket = match listener.accept().await {
+                    Ok((socket, _)) => socket,
+                    Err(e) => {
+                        error!(error = %e, "TLS relay listener failed");
+                        break;
+                    }
+                };
+
+                let connector = connector.clone();
+                let target = target.clone();
+                tokio::spawn(async move {
+                    match target.handshake(&connector, timeout).await {
+                        Ok(mut stream) => {
+                            let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                        }
+                        Err(e) => warn!(host = %target.host, port = target.port, error = %e, "TLS relay connection failed"),
+                    }
+                });
+            }
+        }));
+
+        Ok(local)
+    }
+
+    pub fn close(&self) {
+        for forward in &self.forwards {
+            forward.abort();
+        }
+    }
+}
+
+impl Drop for PinnedRelay {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Where a relayed node is dialled and the name its certificate is checked against.
+struct Target {
+    host: String,
+    port: u16,
+    name: ServerName<'static>,
+}
+
+impl Target {
+    fn new(node: &Url, dial: &Url) -> AppResult<Self> {
+        let host_of = |url: &Url| {
+            url.host_str()
+                .map(|host| host.trim_start_matches('[').trim_end_matches(']').to_string())
+                .ok_or_else(|| AppError::ValidationError(Message::key("config.missing_host").arg("url", url)))
+        };
+        let name = ServerName::try_from(host_of(node)?)
+            .map_err(|e| AppError::ValidationError(Message::key("config.invalid_host_url").arg("error", e)))?;
+        Ok(Target {
+            host: host_of(dial)?,
+            port: dial.port_or_known_default().unwrap_or(443),
+            name,
+        })
+    }
+
+    async fn handshake(&self, connector: &TlsConnector, timeout: Duration) -> io::Result<TlsStream<TcpStream>> {
+        let connect = async {
+            let stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+            connector.connect(self.name.clone(), stream).await
+        };
+        tokio::time::timeout(timeout, connect)
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?
+    }
+}
+
+fn handshake_error(e: io::Error, node: &Url) -> AppError {
+    match e.get_ref().and_then(|inner| inner.downcast_ref::<rustls::Error>()) {
+        Some(rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure)) => {
+            AppError::ConnectionError(Message::key("tls.fingerprint_mismatch").arg("host", node))
+        }
+        Some(tls) => AppError::ConnectionError(Message::key("tls.handshake_failed").arg("error", tls)),
+        None => AppError::ConnectionError(Message::key("connection.connect_failed").arg("url", node).arg("error", e)),
+    }
+}
+
+fn client_config(config: &ElasticsearchConfig, fingerprint: String) -> AppResult<Arc<ClientConfig>> {
+    let provider = Arc::new(crypto::ring::default_provider());
+    let verifier = PinVerifier {
+        fingerprint,
+        mode: config.verification_mode,
+        provider: provider.clone(),
+    };
+    let builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::ConnectionError(Message::key("tls.handshake_failed").arg("error", e)))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier));
+
+    let tls = match non_empty(&config.client_cert_path) {
+        Some(path) => {
+            let (chain, key) = client_identity(path, non_empty(&config.client_cert_password))?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|e| AppError::ValidationError(Message::key("tls.client_cert_invalid").arg("path", path).arg("error", e)))?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    Ok(Arc::new(tls))
+}
+
+/// The certificate chain and private key in a PKCS#12 archive.
+fn client_identity(path: &str, password: Option<&str>) -> AppResult<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let archive = std::fs::read(path)
+        .map_err(|e| AppError::ValidationError(Message::key("tls.client_cert_read_failed").arg("path", path).arg("error", e)))?;
+    let invalid = |e: &dyn std::fmt::Display| {
+        AppError::ValidationError(Message::key("tls.client_cert_invalid").arg("path", path).arg("error", e))
+    };
+
+    let store = KeyStore::from_pkcs12(&archive, password.unwrap_or_default()).map_err(|e| invalid(&e))?;
+    let (_, identity) = store
+        .private_key_chain()
+        .ok_or_else(|| AppError::ValidationError(Message::key("tls.client_cert_no_key").arg("path", path)))?;
+    let chain = identity
+        .chain()
+        .iter()
+        .map(|cert| CertificateDer::from(cert.as_der().to_vec()))
+        .collect();
+    let key = PrivateKeyDer::try_from(identity.key().to_vec()).map_err(|e| invalid(&e))?;
+    Ok((chain, key))
+}
+
+/// Accepts a server only if the pinned certificate is in the chain it presents. The
+/// verification mode then decides whether the chain must lead to the pinned certificate
+/// and whether the leaf must be issued for the host; system roots are never consulted.
+#[derive(Debug)]
+struct PinVerifier {
+    fingerprint: String,
+    mode: VerificationMode,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .find(|cert| fingerprint(cert) == self.fingerprint)
+            .ok_or(rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure))?;
+        if self.mode == VerificationMode::None {
+            return Ok(ServerCertVerified::assertion());
+        }
+
+        let cert = ParsedCertificate::try_from(end_entity)?;
+        // A pinned leaf is trusted as is; anything else must chain up to the pin.
+        if pinned != end_entity {
+            let mut roots = RootCertStore::empty();
+            roots.add(pinned.clone().into_owned())?;
+            verify_server_cert_signed_by_trust_anchor(
+                &cert,
+                &roots,
+                intermediates,
+                now,
+                self.provider.signature_verification_algorithms.all,
+            )?;
+        }
+        if self.mode == VerificationMode::Full {
+            verify_server_name(&cert, server_name)?;
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,175 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::{
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Distribution {
+    Elasticsearch,
+    OpenSearch,
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Distribution::Elasticsearch => write!(f, "Elasticsearch"),
+            Distribution::OpenSearch => write!(f, "OpenSearch"),
+        }
+    }
+}
+
+/// APIs that only exist on some server versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    PointInTime,
+    /// `_shard_doc` as the sort tiebreaker of point in time searches.
+    ShardDocSort,
+}
+
+impl Feature {
+    fn name(&self) -> &'static str {
+        match self {
+            Feature::PointInTime => "Point in time search",
+            Feature::ShardDocSort => "Sorting on _shard_doc",
+        }
+    }
+
+    /// Minimum `(major, minor)` per distribution, `None` when never available.
+    fn minimum(&self, distribution: Distribution) -> Option<(u32, u32)> {
+        match (self, distribution) {
+            (Feature::PointInTime, Distribution::Elasticsearch) => Some((7, 10)),
+            (Feature::PointInTime, Distribution::OpenSearch) => Some((2, 4)),
+            (Feature::ShardDocSort, Distribution::Elasticsearch) => Some((7, 12)),
+            (Feature::ShardDocSort, Distribution::OpenSearch) => None,
+        }
+    }
+}
+
+/// Version and flavor reported by the root endpoint (`GET /`).
+#[derive(Debug, Serialize, Clone)]
+pub struct ServerVersion {
+    pub distribution: Distribution,
+    pub number: String,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    /// `default` or `oss` on Elasticsearch 6.3 to 7.x.
+    pub build_flavor: Option<String>,
+}
+
+impl ServerVersion {
+    pub fn parse(root: &Value) -> AppResult<Self> {
+        let version = &root["version"];
+        let number = version["number"]
+            .as_str()
+            .ok_or_else(|| AppError::ElasticsearchError(Message::key("version.missing")))?;
+        // OpenSearch reports its own version number and sets `distribution`.
+        let distribution = match version["distribution"].as_str() {
+            Some("opensearch") => Distribution::OpenSearch,
+            _ => Distribution::Elasticsearch,
+        };
+
+        // Pre-releases look like `8.0.0-rc1` or `7.17.0-SNAPSHOT`.
+        let mut parts = number
+            .split(['.', '-'])
+            .map(|part| part.parse::<u32>().ok());
+        let mut next = || parts.next().flatten();
+        let major = next()
+            .ok_or_else(|| AppError::ElasticsearchError(Message::key("version.unrecognized").arg("version", number)))?;
+        let minor = next().unwrap_or(0);
+        let patch = next().unwrap_or(0);
+        // OpenSearch 1.x can masquerade as Elasticsearch 7.10.2 for older clients.
+        let (major, minor, patch) = match distribution {
+            Distribution::OpenSearch if major >= 7 => (1, 0, 0),
+            _ => (major, minor, patch),
+        };
+
+        let server = Self {
+            distribution,
+            number: number.to_string(),
+            major,
+            minor,
+            patch,
+            build_flavor: version["build_flavor"].as_str().map(String::from),
+        };
+        if server.distribution == Distribution::Elasticsearch && server.major < 6 {
+            return Err(AppError::UnsupportedError(Message::key("version.too_old").arg("server", server)));
+        }
+
+        Ok(server)
+    }
+
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        feature
+            .minimum(self.distribution)
+            .is_some_and(|(major, minor)| self.at_least(major, minor))
+    }
+
+    pub fn require(&self, feature: Feature) -> AppResult<()> {
+        if self.supports(feature) {
+            return Ok(());
+        }
+        let message = match feature.minimum(self.distribution) {
+            Some((major, minor)) => Message::key("version.feature_requires")
+                .arg("version", format!("{} {}.{}", self.distribution, major, minor)),
+            None => Message::key("version.feature_unavailable").arg("distribution", self.distribution),
+        };
+        Err(AppError::UnsupportedError(message.arg("feature", feature.name()).arg("server", self)))
+    }
+
+    /// Open point in time endpoint for `index` and the field holding the id in its response.
+    pub fn open_point_in_time_path(&self, index: &str) -> (String, &'static str) {
+        match self.distribution {
+            Distribution::Elasticsearch => (format!("/{}/_pit", index), "id"),
+            Distribution::OpenSearch => (format!("/{}/_search/point_in_time", index), "pit_id"),
+        }
+    }
+
+    /// Close point in time endpoint and the body releasing `pit_id`.
+    pub fn close_point_in_time_request(&self, pit_id: &str) -> (&'static str, Value) {
+        match self.distribution {
+            Distribution::Elasticsearch => ("/_pit", json!({ "id": pit_id })),
+            Distribution::OpenSearch => ("/_search/point_in_time", json!({ "pit_id": [pit_id] })),
+        }
+    }
+
+    /// Sort appended to point in time searches so hits with equal sort values keep a
+    /// stable order across pages.
+    pub fn pit_tiebreaker(&self) -> Value {
+        if self.supports(Feature::ShardDocSort) {
+            json!({ "_shard_doc": "asc" })
+        } else {
+            json!({ "_id": "asc" })
+        }
+    }
+
+    /// Elasticsearch 6.x nests mappings under a type name (`{"_doc": {"properties": ...}}`).
+    pub fn has_mapping_types(&self) -> bool {
+        self.distribution == Distribution::Elasticsearch && self.major < 7
+    }
+
+    /// Strips the type level from 6.x mappings so callers always see typeless mappings.
+    pub fn normalize_mappings(&self, mappings: Value) -> Value {
+        if !self.has_mapping_types() {
+            return mappings;
+        }
+        match mappings.as_object() {
+            Some(types) if types.len() == 1 => types.values().next().cloned().unwrap_or(mappings),
+            _ => mappings,
+        }
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.distribution, self.number)
+    }
+}
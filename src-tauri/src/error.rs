@@ -1,25 +1,249 @@
-use serde::Serialize;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use serde_json::Value;
 use std::fmt;
+use crate::i18n::Message;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug)]
 pub enum AppError {
-    ValidationError(String),
-    ConnectionError(String),
-    ElasticsearchError(String),
-    StateError(String),
+    ValidationError(Message),
+    ConnectionError(Message),
+    ElasticsearchError(Message),
+    StateError(Message),
+    ReadOnlyError(Message),
+    UnsupportedError(Message),
+    CancelledError(Message),
+    /// Elasticsearch answered with a non-2xx status.
+    ResponseError(Box<ResponseError>),
 }
 
-impl fmt::Display for AppError {
+/// One link of an Elasticsearch error chain (`error`, `root_cause[]`, `caused_by`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorCause {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub caused_by: Option<Box<ErrorCause>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedRequest {
+    pub method: String,
+    /// Path and query string.
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseError {
+    pub status: u16,
+    pub error: Option<ErrorCause>,
+    pub root_cause: Vec<ErrorCause>,
+    pub request: FailedRequest,
+    /// Raw body when it is not an Elasticsearch error document.
+    pub body: Option<String>,
+}
+
+impl ResponseError {
+    /// Decodes an error body: the usual `{"error": {...}}` document, the older
+    /// `{"error": "reason"}` form, or anything else kept as raw text.
+    pub fn parse(status: u16, method: &str, path: &str, body: &str) -> Self {
+        let request = FailedRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+        };
+        let document = serde_json::from_str::<Value>(body).unwrap_or(Value::Null);
+
+        let (error, root_cause) = match &document["error"] {
+            error @ Value::Object(_) => {
+                let root_cause = error["root_cause"]
+                    .as_array()
+                    .map(|causes| {
+                        causes.iter()
+                            .filter_map(|cause| serde_json::from_value(cause.clone()).ok())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (serde_json::from_value::<ErrorCause>(error.clone()).ok(), root_cause)
+            }
+            Value::String(reason) => (
+                Some(ErrorCause { kind: "error".to_string(), reason: Some(reason.clone()), caused_by: None }),
+                Vec::new(),
+            ),
+            _ => (None, Vec::new()),
+        };
+
+        let body = match error {
+            None if !body.trim().is_empty() => Some(body.to_string()),
+            _ => None,
+        };
+        Self { status, error, root_cause, request, body }
+    }
+
+    /// Reads and decodes the body of a failed response.
+    pub async fn from_response(response: elasticsearch::http::response::Response) -> Self {
+        let status = response.status_code().as_u16();
+        let method = format!("{:?}", response.method()).to_uppercase();
+        let url = response.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let body = response.text().await.unwrap_or_default();
+        Self::parse(status, &method, &path, &body)
+    }
+
+    /// The innermost reason in the `caused_by` chain.
+    pub fn reason(&self) -> Option<&str> {
+        let mut cause = self.error.as_ref()?;
+        let mut reason = cause.reason.as_deref();
+        while let Some(next) = cause.caused_by.as_deref() {
+            reason = next.reason.as_deref().or(reason);
+            cause = next;
+        }
+        reason
+    }
+
+    fn code(&self) -> &'static str {
+        match self.status {
+            400 => "bad_request",
+            401 => "unauthorized",
+            403 => "forbidden",
+            404 => "not_found",
+            409 => "conflict",
+            429 => "too_many_requests",
+            500..=599 => "server_error",
+            _ => "response_error",
+        }
+    }
+}
+
+impl fmt::Display for ResponseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = Message::key("error.response")
+            .arg("method", &self.request.method)
+            .arg("path", &self.request.path)
+            .arg("status", self.status);
+        write!(f, "{}", summary)?;
+        if let Some(error) = &self.error {
+            write!(f, " [{}]", error.kind)?;
+        }
+        if let Some(reason) = self.reason() {
+            write!(f, ": {}", reason)?;
+        } else if let Some(body) = &self.body {
+            write!(f, ": {}", body)?;
+        }
+        Ok(())
+    }
+}
+
+impl AppError {
+    /// Machine-readable code the frontend can branch on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::ValidationError(_) => "validation_error",
+            AppError::ConnectionError(_) => "connection_error",
+            AppError::ElasticsearchError(_) => "elasticsearch_error",
+            AppError::StateError(_) => "state_error",
+            AppError::ReadOnlyError(_) => "read_only",
+            AppError::UnsupportedError(_) => "unsupported",
+            AppError::CancelledError(_) => "cancelled",
+            AppError::ResponseError(e) => e.code(),
+        }
+    }
+
+    /// Catalog key of the category label shown before the message.
+    fn label_key(&self) -> &'static str {
         match self {
-            AppError::ValidationError(msg) => write!(f, "验证错误: {}", msg),
-            AppError::ConnectionError(msg) => write!(f, "连接错误: {}", msg),
-            AppError::ElasticsearchError(msg) => write!(f, "Elasticsearch错误: {}", msg),
-            AppError::StateError(msg) => write!(f, "状态错误: {}", msg),
+            AppError::ValidationError(_) => "error.validation_error",
+            AppError::ConnectionError(_) => "error.connection_error",
+            AppError::ElasticsearchError(_) | AppError::ResponseError(_) => "error.elasticsearch_error",
+            AppError::StateError(_) => "error.state_error",
+            AppError::ReadOnlyError(_) => "error.read_only",
+            AppError::UnsupportedError(_) => "error.unsupported",
+            AppError::CancelledError(_) => "error.cancelled",
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            AppError::ResponseError(e) => Some(e.status),
+            _ => None,
+        }
+    }
+}
+
+impl AppError {
+    /// The message without its category label.
+    pub fn detail(&self) -> String {
+        match (self, self.message()) {
+            (AppError::ResponseError(e), _) => e.to_string(),
+            (_, Some(msg)) => msg.to_string(),
+            (_, None) => String::new(),
+        }
+    }
+
+    fn message(&self) -> Option<&Message> {
+        match self {
+            AppError::ValidationError(msg)
+            | AppError::ConnectionError(msg)
+            | AppError::ElasticsearchError(msg)
+            | AppError::StateError(msg)
+            | AppError::ReadOnlyError(msg)
+            | AppError::UnsupportedError(msg)
+            | AppError::CancelledError(msg) => Some(msg),
+            AppError::ResponseError(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = Message::key(self.label_key());
+        match (self, self.message()) {
+            (AppError::ResponseError(e), _) => write!(f, "{}: {}", label, e),
+            (_, Some(msg)) => write!(f, "{}: {}", label, msg),
+            (_, None) => write!(f, "{}", label),
+        }
+    }
+}
+
+/// Every command fails with `{ code, message, key, params, status, details }`. `message`
+/// is rendered in the selected locale; `key` and `params` are set for catalog messages.
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let details = match self {
+            AppError::ResponseError(e) => Some(e.as_ref()),
+            _ => None,
+        };
+        let message = self.message();
+        let mut state = serializer.serialize_struct("AppError", 6)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("key", &message.and_then(Message::key_name))?;
+        state.serialize_field("params", &message.and_then(Message::params))?;
+        state.serialize_field("status", &self.status())?;
+        state.serialize_field("details", &details)?;
+        state.end()
+    }
+}
+
+impl From<ResponseError> for AppError {
+    fn from(e: ResponseError) -> Self {
+        AppError::ResponseError(Box::new(e))
+    }
+}
+
+impl From<elasticsearch::Error> for AppError {
+    fn from(e: elasticsearch::Error) -> Self {
+        if e.is_timeout() || (e.status_code().is_none() && !e.is_json()) {
+            AppError::ConnectionError(Message::key("connection.transport_failed").arg("error", e))
+        } else {
+            AppError::ElasticsearchError(Message::key("response.request_failed").arg("error", e))
         }
     }
 }
 
 impl std::error::Error for AppError {}
 
-pub type AppResult<T> = Result<T, AppError>; 
\ No newline at end of file
+pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,580 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Emitter};
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufWriter},
+    sync::oneshot,
+};
+use tracing::{debug, warn};
+use crate::{
+    elasticsearch::{client::ESClient, version::Feature},
+    error::{AppError, AppResult},
+    i18n::Message,
+    paging,
+};
+
+/// Event emitted as an export makes progress and once it ends.
+pub const EXPORT_EVENT: &str = "export-progress";
+/// Hits fetched per request.
+pub const BATCH_SIZE: u64 = 1000;
+/// How long the point in time or scroll stays open between batches.
+const KEEP_ALIVE: Duration = Duration::from_secs(300);
+/// Hit metadata that can be selected as columns next to source fields.
+pub const META_COLUMNS: [&str; 3] = ["_index", "_id", "_score"];
+/// Metadata written when no columns are selected.
+const DEFAULT_META: [&str; 2] = ["_index", "_id"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFormat {
+    /// One JSON document per line.
+    Ndjson,
+    /// A single JSON array.
+    Json,
+    /// Comma separated values with nested fields flattened by dotted path.
+    Csv,
+}
+
+/// Where and how an export is written.
+#[derive(Debug, Deserialize)]
+pub struct ExportTarget {
+    pub format: ResultFormat,
+    pub path: PathBuf,
+    pub columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    pub index: String,
+    pub query: Value,
+    pub format: ResultFormat,
+    pub path: PathBuf,
+    /// Dotted source fields and metadata columns to write, in order. Without a
+    /// selection documents are written whole, and CSV columns follow the first batch.
+    pub columns: Option<Vec<String>>,
+}
+
+/// Outcome of a finished export.
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub written: u64,
+    /// CSV fields first seen after the header was written, and so left out.
+    pub dropped_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExportProgress {
+    pub export_id: String,
+    pub state: ExportState,
+    /// Documents written so far.
+    pub written: u64,
+    /// Documents matching the query, when the cluster reports an exact count.
+    pub total: Option<u64>,
+    pub path: String,
+    pub error: Option<Value>,
+    /// Fields missing from a CSV export whose columns came from the first batch.
+    pub dropped_columns: Vec<String>,
+}
+
+/// Exports running in the background, cancellable by id.
+#[derive(Default)]
+pub struct ExportJobs {
+    next_id: AtomicU64,
+    running: Arc<Mutex<HashMap<String, oneshot::Sender<()>>>>,
+}
+
+impl ExportJobs {
+    /// Starts exporting `request` and returns the export id. Progress is reported
+    /// through [`EXPORT_EVENT`].
+    pub fn start(&self, app: AppHandle, client: Arc<ESClient>, request: ExportRequest) -> AppResult<String> {
+        if !request.query.is_object() {
+            return Err(AppError::ValidationError(Message::key("search.query_not_object")));
+        }
+        if request.path.file_name().is_none() {
+            return Err(AppError::ValidationError(Message::key("export.path_not_file")));
+        }
+
+        let export_id = format!("export-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let (cancel, cancelled) = oneshot::channel();
+        self.running.lock().expect("lock poisoned").insert(export_id.clone(), cancel);
+
+        let running = self.running.clone();
+        let id = export_id.clone();
+        tokio::spawn(async move {
+            let mut progress = ExportProgress {
+                export_id: id.clone(),
+                state: ExportState::Running,
+                written: 0,
+                total: None,
+                path: request.path.display().to_string(),
+                error: None,
+                dropped_columns: Vec::new(),
+            };
+            emit(&app, &progress);
+
+            let result = export(&client, &request, cancelled, |written, total| {
+                progress.written = written;
+                progress.total = total;
+                emit(&app, &progress);
+            })
+            .await;
+            running.lock().expect("lock poisoned").remove(&id);
+
+            match result {
+                Ok(summary) => {
+                    debug!(export_id = id, written = summary.written, "Export completed");
+                    if !summary.dropped_columns.is_empty() {
+                        warn!(export_id = id, columns = ?summary.dropped_columns, "Fields left out of CSV export");
+                    }
+                    progress.state = ExportState::Completed;
+                    progress.written = summary.written;
+                    progress.dropped_columns = summary.dropped_columns;
+                }
+                Err(AppError::CancelledError(_)) => progress.state = ExportState::Cancelled,
+                Err(e) => {
+                    warn!(export_id = id, error = %e, "Export failed");
+                    progress.state = ExportState::Failed;
+                    progress.error = serde_json::to_value(&e).ok();
+                }
+            }
+            emit(&app, &progress);
+        });
+
+        Ok(export_id)
+    }
+
+    /// Stops the export, removing its partial file. Returns whether it was running.
+    pub fn cancel(&self, export_id: &str) -> bool {
+        match self.running.lock().expect("lock poisoned").remove(export_id) {
+            Some(cancel) => cancel.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+fn emit(app: &AppHandle, progress: &ExportProgress) {
+    if let Err(e) = app.emit(EXPORT_EVENT, progress.clone()) {
+        warn!(event = EXPORT_EVENT, error = %e, "Failed to emit event");
+    }
+}
+
+/// Writes every hit of `request` to its file.
+/// `progress` gets the written and total counts after each batch. The file only
+/// appears once complete: it is written next to its destination and moved there.
+pub async fn export(
+    client: &ESClient,
+    request: &ExportRequest,
+    cancelled: oneshot::Receiver<()>,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> AppResult<ExportSummary> {
+    // A dropped sender leaves the export running.
+    let cancelled = async move {
+        if cancelled.await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    tokio::pin!(cancelled);
+
+    let part_path = part_path(&request.path);
+    let mut walker = Walker::open(client, request).await?;
+    let result = async {
+        let mut writer = DocumentWriter::create(&part_path, request).await?;
+        loop {
+            let hits = tokio::select! {
+                hits = walker.next() => hits?,
+                _ = &mut cancelled => {
+                    return Err(AppError::CancelledError(
+                        Message::key("export.cancelled").arg("path", request.path.display())
+                    ));
+                }
+            };
+            if hits.is_empty() {
+                break;
+            }
+            writer.write(&hits).await?;
+            progress(writer.written, walker.total);
+        }
+        writer.finish().await
+    }
+    .await;
+    walker.close().await;
+
+    match result {
+        Ok(summary) => {
+            tokio::fs::rename(&part_path, &request.path)
+                .await
+                .map_err(|e| write_error(&request.path, e))?;
+            Ok(summary)
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            Err(e)
+        }
+    }
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+fn write_error(path: &Path, e: std::io::Error) -> AppError {
+    AppError::StateError(Message::key("file.write_failed").arg("path", path.display()).arg("error", e))
+}
+
+/// Position in the result set: a point in time searched with `search_after`, or a
+/// scroll on servers without point in time.
+enum Position {
+    PointInTime { pit_id: String, search_after: Option<Value> },
+    Scroll { scroll_id: Option<String> },
+}
+
+/// Fetches all hits of a query in batches of [`BATCH_SIZE`].
+struct Walker<'a> {
+    client: &'a ESClient,
+    index: &'a str,
+    body: Value,
+    position: Position,
+    total: Option<u64>,
+    exhausted: bool,
+}
+
+impl<'a> Walker<'a> {
+    async fn open(client: &'a ESClient, request: &'a ExportRequest) -> AppResult<Self> {
+        let version = client.server_version()?;
+        let point_in_time = version.supports(Feature::PointInTime);
+
+        let mut body = request.query.clone();
+        body["size"] = json!(BATCH_SIZE);
+        if let Some(columns) = &request.columns {
+            let fields: Vec<&String> = columns
+                .iter()
+                .filter(|column| !META_COLUMNS.contains(&column.as_str()))
+                .collect();
+            body["_source"] = if fields.is_empty() { json!(false) } else { json!(fields) };
+        }
+        let tiebreaker = if point_in_time {
+            version.pit_tiebreaker()
+        } else {
+            json!({ "_doc": "asc" })
+        };
+        let (body, _) = paging::prepare(body, tiebreaker)?;
+
+        let position = if point_in_time {
+            let pit_id = client
+                .open_point_in_time(&request.index, &paging::keep_alive_param(KEEP_ALIVE))
+                .await?;
+            Position::PointInTime { pit_id, search_after: None }
+        } else {
+            Position::Scroll { scroll_id: None }
+        };
+
+        Ok(Self {
+            client,
+            index: &request.index,
+            body,
+            position,
+            total: None,
+            exhausted: false,
+        })
+    }
+
+    /// Next batch of hits, empty once all were returned.
+    async fn next(&mut self) -> AppResult<Vec<Value>> {
+        if self.exhausted {
+            return Ok(Vec::new());
+        }
+        let first = self.total.is_none();
+
+        let mut response = match &mut self.position {
+            Position::PointInTime { pit_id, search_after } => {
+                let mut body = self.body.clone();
+                body["pit"] = json!({ "id": pit_id, "keep_alive": paging::keep_alive_param(KEEP_ALIVE) });
+                if first {
+                    body["track_total_hits"] = json!(true);
+                }
+                if let Some(after) = search_after {
+                    body["search_after"] = after.clone();
+                }
+                let response = self.client.search_point_in_time(body, None, None).await?;
+                if let Some(id) = response["pit_id"].as_str() {
+                    *pit_id = id.to_string();
+                }
+                response
+            }
+            Position::Scroll { scroll_id } => {
+                let keep_alive = paging::keep_alive_param(KEEP_ALIVE);
+                let response = match scroll_id {
+                    Some(id) => self.client.scroll(id, &keep_alive).await?,
+                    None => self.client.search_scroll(self.index, self.body.clone(), &keep_alive).await?,
+                };
+                if let Some(id) = response["_scroll_id"].as_str() {
+                    *scroll_id = Some(id.to_string());
+                }
+                response
+            }
+        };
+
+        if first {
+            self.total = exact_total(&response["hits"]["total"]);
+        }
+        let hits = match response["hits"]["hits"].take() {
+            Value::Array(hits) => hits,
+            _ => Vec::new(),
+        };
+        match &mut self.position {
+            Position::PointInTime { search_after, .. } => {
+                // A short batch is the last one; no need to ask for an empty page.
+                self.exhausted = (hits.len() as u64) < BATCH_SIZE;
+                *search_after = hits.last().map(|hit| hit["sort"].clone());
+            }
+            Position::Scroll { .. } => self.exhausted = hits.is_empty(),
+        }
+        Ok(hits)
+    }
+
+    /// Releases the point in time or scroll. Failures are only logged: the cluster
+    /// drops them by itself once their keep-alive lapses.
+    async fn close(self) {
+        let result = match &self.position {
+            Position::PointInTime { pit_id, .. } => self.client.close_point_in_time(pit_id).await,
+            Position::Scroll { scroll_id: Some(id) } => self.client.clear_scroll(id).await,
+            Position::Scroll { scroll_id: None } => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!(error = %e, "Failed to release export search context");
+        }
+    }
+}
+
+/// `hits.total` as a count, unless it is only a lower bound. 6.x reports a number.
+fn exact_total(total: &Value) -> Option<u64> {
+    match total {
+        Value::Number(count) => count.as_u64(),
+        Value::Object(total) if total.get("relation").and_then(Value::as_str) == Some("eq") => {
+            total.get("value").and_then(Value::as_u64)
+        }
+        _ => None,
+    }
+}
+
+struct DocumentWriter<'a> {
+    out: BufWriter<File>,
+    path: &'a Path,
+    format: ResultFormat,
+    /// Metadata copied from each hit into its document.
+    meta: Vec<&'static str>,
+    /// CSV columns, resolved from the first batch when none were selected.
+    columns: Option<Vec<String>>,
+    /// Whether `columns` were discovered rather than selected.
+    discovered: bool,
+    /// Fields of later batches missing from discovered columns.
+    dropped: BTreeSet<String>,
+    written: u64,
+}
+
+impl<'a> DocumentWriter<'a> {
+    async fn create(path: &'a Path, request: &ExportRequest) -> AppResult<Self> {
+        let file = File::create(path).await.map_err(|e| write_error(path, e))?;
+        let meta = match &request.columns {
+            Some(columns) => META_COLUMNS
+                .into_iter()
+                .filter(|meta| columns.iter().any(|column| column == meta))
+                .collect(),
+            None => DEFAULT_META.to_vec(),
+        };
+        let mut writer = Self {
+            out: BufWriter::new(file),
+            path,
+            format: request.format,
+            meta,
+            columns: request.columns.clone(),
+            discovered: false,
+            dropped: BTreeSet::new(),
+            written: 0,
+        };
+        match writer.format {
+            ResultFormat::Json => writer.put(b"[\n").await?,
+            // Lets spreadsheet applications detect UTF-8.
+            ResultFormat::Csv => writer.put("\u{feff}".as_bytes()).await?,
+            ResultFormat::Ndjson => {}
+        }
+        Ok(writer)
+    }
+
+    async fn write(&mut self, hits: &[Value]) -> AppResult<()> {
+        let documents: Vec<Map<String, Value>> = hits.iter().map(|hit| self.document(hit)).collect();
+        let mut chunk = String::new();
+        match self.format {
+            ResultFormat::Ndjson => {
+                for document in &documents {
+                    chunk.push_str(&Value::Object(document.clone()).to_string());
+                    chunk.push('\n');
+                }
+            }
+            ResultFormat::Json => {
+                for document in &documents {
+                    if self.written > 0 || !chunk.is_empty() {
+                        chunk.push_str(",\n");
+                    }
+                    chunk.push_str(&Value::Object(document.clone()).to_string());
+                }
+            }
+            ResultFormat::Csv => {
+                let rows: Vec<Map<String, Value>> = documents.iter().map(flatten).collect();
+                let columns = match &self.columns {
+                    Some(columns) => columns.clone(),
+                    None => {
+                        let columns = discover_columns(&self.meta, &rows);
+                        self.columns = Some(columns.clone());
+                        self.discovered = true;
+                        columns
+                    }
+                };
+                if self.discovered {
+                    let known: HashSet<&String> = columns.iter().collect();
+                    self.dropped.extend(
+                        rows.iter().flat_map(|row| row.keys()).filter(|key| !known.contains(key)).cloned()
+                    );
+                }
+                if self.written == 0 {
+                    chunk.push_str(&csv_row(columns.iter().map(String::as_str)));
+                }
+                for (document, row) in documents.iter().zip(&rows) {
+                    // Selected columns may name an object, written whole as JSON.
+                    let cells: Vec<String> = columns
+                        .iter()
+                        .map(|column| row.get(column).or_else(|| field(document, column)))
+                        .map(|value| value.map(csv_value).unwrap_or_default())
+                        .collect();
+                    chunk.push_str(&csv_row(cells.iter().map(String::as_str)));
+                }
+            }
+        }
+        self.put(chunk.as_bytes()).await?;
+        self.written += hits.len() as u64;
+        Ok(())
+    }
+
+    /// The document written for `hit`: its source with the selected metadata.
+    fn document(&self, hit: &Value) -> Map<String, Value> {
+        let mut document = Map::new();
+        for meta in &self.meta {
+            if let Some(value) = hit.get(*meta).filter(|value| !value.is_null()) {
+                document.insert(meta.to_string(), value.clone());
+            }
+        }
+        if let Some(Value::Object(source)) = hit.get("_source") {
+            document.extend(source.clone());
+        }
+        document
+    }
+
+    async fn finish(mut self) -> AppResult<ExportSummary> {
+        match self.format {
+            ResultFormat::Json if self.written > 0 => self.put(b"\n]\n").await?,
+            ResultFormat::Json => self.put(b"]\n").await?,
+            // An empty result still gets a header when columns were selected.
+            ResultFormat::Csv if self.written == 0 => {
+                if let Some(columns) = self.columns.take() {
+                    self.put(csv_row(columns.iter().map(String::as_str)).as_bytes()).await?;
+                }
+            }
+            _ => {}
+        }
+        self.out.flush().await.map_err(|e| write_error(self.path, e))?;
+        Ok(ExportSummary {
+            written: self.written,
+            dropped_columns: self.dropped.into_iter().collect(),
+        })
+    }
+
+    async fn put(&mut self, bytes: &[u8]) -> AppResult<()> {
+        self.out.write_all(bytes).await.map_err(|e| write_error(self.path, e))
+    }
+}
+
+/// Flattens nested objects into dotted paths; arrays are kept as values.
+fn flatten(document: &Map<String, Value>) -> Map<String, Value> {
+    fn walk(prefix: &str, value: &Value, out: &mut Map<String, Value>) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    walk(&format!("{}.{}", prefix, key), value, out);
+                }
+            }
+            value => {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+
+    let mut out = Map::new();
+    for (key, value) in document {
+        walk(key, value, &mut out);
+    }
+    out
+}
+
+/// Value at the dotted `path` of `document`.
+fn field<'d>(document: &'d Map<String, Value>, path: &str) -> Option<&'d Value> {
+    let mut segments = path.split('.');
+    let mut value = document.get(segments.next()?)?;
+    for segment in segments {
+        value = value.get(segment)?;
+    }
+    Some(value)
+}
+
+/// The metadata columns followed by every field found in `rows`, in order of appearance.
+fn discover_columns(meta: &[&str], rows: &[Map<String, Value>]) -> Vec<String> {
+    let mut columns: Vec<String> = meta.iter().map(|meta| meta.to_string()).collect();
+    let mut seen: HashSet<String> = columns.iter().cloned().collect();
+    for row in rows {
+        for key in row.keys() {
+            if seen.insert(key.clone()) {
+                columns.push(key.clone());
+            }
+        }
+    }
+    columns
+}
+
+/// Cell text of a value: strings as is, arrays and objects as JSON, null as empty.
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn csv_row<'v>(cells: impl Iterator<Item = &'v str>) -> String {
+    let mut row = cells.map(csv_escape).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+/// Locale messages are rendered in.
+pub fn locale() -> Locale {
+    *LOCALE.read().expect("lock poisoned")
+}
+
+pub fn set_locale(locale: Locale) {
+    *LOCALE.write().expect("lock poisoned") = locale;
+}
+
+type Catalog = HashMap<String, String>;
+
+fn catalog(locale: Locale) -> &'static Catalog {
+    static EN: OnceLock<Catalog> = OnceLock::new();
+    static ZH_CN: OnceLock<Catalog> = OnceLock::new();
+    match locale {
+        Locale::En => EN.get_or_init(|| parse(include_str!("../locales/en.json"))),
+        Locale::ZhCn => ZH_CN.get_or_init(|| parse(include_str!("../locales/zh-CN.json"))),
+    }
+}
+
+fn parse(source: &str) -> Catalog {
+    serde_json::from_str(source).expect("invalid message catalog")
+}
+
+/// Looks `key` up in the current locale, falling back to English and then to the key.
+fn template(key: &str) -> &str {
+    catalog(locale())
+        .get(key)
+        .or_else(|| catalog(Locale::En).get(key))
+        .map(String::as_str)
+        .unwrap_or(key)
+}
+
+/// Renders `key` in the current locale, replacing `{name}` with the matching param.
+pub fn translate(key: &str, params: &BTreeMap<String, String>) -> String {
+    let mut output = template(key).to_string();
+    for (name, value) in params {
+        output = output.replace(&format!("{{{}}}", name), value);
+    }
+    output
+}
+
+/// A user-facing message: either a catalog key with params, or text that is shown as is
+/// (details coming from libraries or the cluster).
+#[derive(Debug, Clone)]
+pub enum Message {
+    Text(String),
+    Key {
+        key: &'static str,
+        params: BTreeMap<String, String>,
+    },
+}
+
+impl Message {
+    pub fn key(key: &'static str) -> Self {
+        Message::Key { key, params: BTreeMap::new() }
+    }
+
+    pub fn arg(mut self, name: &str, value: impl fmt::Display) -> Self {
+        if let Message::Key { params, .. } = &mut self {
+            params.insert(name.to_string(), value.to_string());
+        }
+        self
+    }
+
+    pub fn key_name(&self) -> Option<&'static str> {
+        match self {
+            Message::Key { key, .. } => Some(key),
+            Message::Text(_) => None,
+        }
+    }
+
+    pub fn params(&self) -> Option<&BTreeMap<String, String>> {
+        match self {
+            Message::Key { params, .. } => Some(params),
+            Message::Text(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Text(text) => f.write_str(text),
+            Message::Key { key, params } => f.write_str(&translate(key, params)),
+        }
+    }
+}
+
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Message::Text(text)
+    }
+}
+
+impl From<&str> for Message {
+    fn from(text: &str) -> Self {
+        Message::Text(text.to_string())
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+use crate::{
+    config::{is_secret_header, non_empty, ElasticsearchConfig, VerificationMode},
+    db::Database,
+    error::AppResult,
+};
+
+/// Requests kept per connection, in memory and in the database.
+pub const CAPACITY: usize = 500;
+/// Replaces secrets in recorded bodies.
+pub const REDACTED: &str = "********";
+/// Last words of body keys whose values are never recorded, e.g. `session_token`.
+const SECRET_WORDS: &[&str] = &["password", "passwd", "secret", "token", "credential", "credentials", "apikey"];
+/// Key endings naming secrets whose last word alone (`key`) is too common.
+const SECRET_SUFFIXES: &[&str] = &["api_key", "access_key", "private_key", "secret_key", "account_key"];
+
+/// A request issued by an `ESClient`, as shown by the request inspector.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestRecord {
+    pub id: u64,
+    pub connection_id: String,
+    /// Unix timestamp in milliseconds.
+    pub timestamp: i64,
+    pub method: String,
+    /// Node the request was sent to, e.g. `https://es01:9200`.
+    pub node: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub body: Option<String>,
+    /// Absent when no response was received.
+    pub status: Option<u16>,
+    /// Response size in bytes, as announced by `Content-Length`.
+    pub response_size: Option<u64>,
+    pub duration_ms: u64,
+    /// Transport error for requests that got no response.
+    pub error: Option<String>,
+}
+
+impl RequestRecord {
+    pub fn failed(&self) -> bool {
+        !matches!(self.status, Some(status) if (200..300).contains(&status))
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RequestFilter {
+    pub method: Option<String>,
+    /// Matches paths containing this text.
+    pub path: Option<String>,
+    /// Only transport errors and non-2xx responses.
+    #[serde(default)]
+    pub failed_only: bool,
+    pub limit: Option<usize>,
+}
+
+impl RequestFilter {
+    fn matches(&self, record: &RequestRecord) -> bool {
+        self.method.as_ref().is_none_or(|method| method.eq_ignore_ascii_case(&record.method))
+            && self.path.as_ref().is_none_or(|path| record.path.contains(path.as_str()))
+            && (!self.failed_only || record.failed())
+    }
+}
+
+/// Bounded history of the requests made on each connection, optionally written
+/// through to the database so it survives restarts.
+#[derive(Default)]
+pub struct RequestInspector {
+    next_id: AtomicU64,
+    records: Mutex<HashMap<String, VecDeque<RequestRecord>>>,
+    store: Option<Database>,
+    persist: AtomicBool,
+}
+
+impl RequestInspector {
+    pub fn new(store: Option<Database>) -> Self {
+        Self {
+            store,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_persist(&self, persist: bool) {
+        self.persist.store(persist, Ordering::Relaxed);
+    }
+
+    /// Assigns `record` an id and stores it, dropping the oldest entry of a full buffer.
+    pub fn record(&self, mut record: RequestRecord) {
+        record.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.push(record.clone());
+
+        if let Some(store) = self.store.clone().filter(|_| self.persist.load(Ordering::Relaxed)) {
+            tokio::spawn(async move {
+                if let Err(e) = store.save_request(&record, CAPACITY).await {
+                    warn!(error = %e, "Failed to persist request");
+                }
+            });
+        }
+    }
+
+    /// Loads persisted requests, oldest first, giving them fresh ids.
+    pub fn restore(&self, records: Vec<RequestRecord>) {
+        for mut record in records {
+            record.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            self.push(record);
+        }
+    }
+
+    fn push(&self, record: RequestRecord) {
+        let mut records = self.records.lock().expect("lock poisoned");
+        let buffer = records.entry(record.connection_id.clone()).or_default();
+        if buffer.len() == CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(record);
+    }
+
+    /// Matching requests of `connection_id`, newest first.
+    pub fn list(&self, connection_id: &str, filter: &RequestFilter) -> Vec<RequestRecord> {
+        let records = self.records.lock().expect("lock poisoned");
+        records
+            .get(connection_id)
+            .map(|buffer| {
+                buffer.iter()
+                    .rev()
+                    .filter(|record| filter.matches(record))
+                    .take(filter.limit.unwrap_or(CAPACITY))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, connection_id: &str, id: u64) -> Option<RequestRecord> {
+        let records = self.records.lock().expect("lock poisoned");
+        records.get(connection_id)?.iter().find(|record| record.id == id).cloned()
+    }
+
+    /// Forgets the requests of `connection_id`, including persisted ones.
+    pub async fn clear(&self, connection_id: &str) -> AppResult<()> {
+        self.records.lock().expect("lock poisoned").remove(connection_id);
+        if let Some(store) = &self.store {
+            store.clear_requests(connection_id.to_string()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Copy of a request body for recording, with the values of `fields` and of any
+/// secret-looking key masked, so credentials never reach the inspector or its table.
+pub fn redact_body(body: &Value, fields: &[&str]) -> Value {
+    let mut body = body.clone();
+    if let Value::Object(object) = &mut body {
+        for field in fields {
+            if let Some(value) = object.get_mut(*field) {
+                *value = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    redact_secrets(&mut body);
+    body
+}
+
+fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if is_secret_key(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_secrets(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase().replace(['-', '.'], "_");
+    let last = key.rsplit('_').next().unwrap_or_default();
+    SECRET_WORDS.contains(&last) || SECRET_SUFFIXES.iter().any(|suffix| key.ends_with(suffix))
+}
+
+/// Renders `record` as a curl command. Credentials are never included: basic auth
+/// makes curl prompt for the password, and API keys and tokens are read from
+/// `ES_API_KEY` (base64 encoded) / `ES_TOKEN`. Secret custom headers are read from
+/// `ES_HEADER_<NAME>`, e.g. `ES_HEADER_X_TENANT_TOKEN`.
+pub fn to_curl(record: &RequestRecord, config: Option<&ElasticsearchConfig>) -> String {
+    let mut url = format!("{}{}", record.node.trim_end_matches('/'), record.path);
+    if let Some(query) = &record.query {
+        url.push('?');
+        url.push_str(query);
+    }
+
+    let mut parts = vec!["curl".to_string()];
+    match record.method.as_str() {
+        "GET" => {}
+        "HEAD" => parts.push("-I".to_string()),
+        method => parts.push(format!("-X {}", method)),
+    }
+    if let Some(config) = config {
+        if config.verification_mode == VerificationMode::None {
+            parts.push("-k".to_string());
+        } else if let Some(ca_cert) = non_empty(&config.ca_cert_path) {
+            parts.push(format!("--cacert {}", shell_quote(ca_cert)));
+        }
+        if let Some(username) = non_empty(&config.username) {
+            parts.push(format!("-u {}", shell_quote(username)));
+        } else if non_empty(&config.api_key).is_some() {
+            parts.push("-H \"Authorization: ApiKey $ES_API_KEY\"".to_string());
+        } else if non_empty(&config.bearer_token).is_some() {
+            parts.push("-H \"Authorization: Bearer $ES_TOKEN\"".to_string());
+        }
+        for (name, header) in &config.headers {
+            let name = name.trim();
+            if is_secret_header(name, header) {
+                parts.push(format!("-H \"{}: ${}\"", name, header_variable(name)));
+            } else {
+                let value = header.value.as_deref().unwrap_or_default();
+                parts.push(format!("-H {}", shell_quote(&format!("{}: {}", name, value))));
+            }
+        }
+    }
+    parts.push(shell_quote(&url));
+    if let Some(body) = &record.body {
+        parts.push("-H 'Content-Type: application/json'".to_string());
+        parts.push(format!("-d {}", shell_quote(body)));
+    }
+    parts.join(" ")
+}
+
+fn header_variable(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("ES_HEADER_{}", name)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
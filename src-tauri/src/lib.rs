@@ -1,8 +1,17 @@
 pub mod config;
+pub mod crypto;
 pub mod db;
 pub mod elasticsearch;
 pub mod error;
+pub mod export;
+pub mod i18n;
+pub mod inspector;
+pub mod logging;
+pub mod paging;
+pub mod profiles;
+pub mod requests;
 pub mod commands;
+pub mod transfer;
 mod state;
 
-pub use state::AppState;
+pub use state::{AppState, ClientMap};
@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Debug};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    field::MakeExt,
+    filter::Targets,
+    fmt::{
+        format::{self, FmtSpan, Writer},
+        MakeWriter,
+    },
+    layer::Context,
+    prelude::*,
+    registry::LookupSpan,
+    Layer,
+};
+
+/// Event carrying each [`LogEntry`] to the frontend.
+pub const LOG_EVENT: &str = "log-entry";
+const LOG_FILE: &str = "elastic-eye.log";
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept next to the active one (`elastic-eye.log.1` is the newest).
+const KEEP_FILES: usize = 5;
+const RECENT_CAPACITY: usize = 1000;
+/// Used when `RUST_LOG` is unset or not a plain `target=level` list.
+const DEFAULT_FILTER: &str = "warn,elastic_eye=info,elastic_eye_lib=info";
+/// Field names whose values never reach stdout, the log file or the log stream.
+const SECRET_FIELDS: &[&str] = &["password", "passphrase", "api_key", "token", "secret", "authorization", "cookie"];
+const REDACTED: &str = "********";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LogEntry {
+    /// Unix timestamp in milliseconds.
+    pub timestamp: i64,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+    /// Event fields merged with those of the enclosing spans, e.g. `connection_id`.
+    pub fields: BTreeMap<String, String>,
+}
+
+static RECENT: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Installs the global subscriber: stdout, a rotating file in `log_dir`, and the
+/// frontend log stream.
+pub fn init(log_dir: &Path, app: AppHandle) -> io::Result<()> {
+    let file = RotatingFile::open(log_dir)?;
+    let _ = LOG_DIR.set(log_dir.to_path_buf());
+    let _ = APP.set(app);
+
+    let filter = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|value| value.parse::<Targets>().ok())
+        .unwrap_or_else(|| DEFAULT_FILTER.parse().expect("valid default filter"));
+
+    let fields = format::debug_fn(format_field).delimited(" ");
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .fmt_fields(fields.clone())
+                .with_filter(filter.clone()),
+        )
+        .with(
+            tracing_subscriber::fmt::layer()
+                .fmt_fields(fields)
+                .with_ansi(false)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(file)
+                .with_filter(filter.clone()),
+        )
+        .with(LogStream.with_filter(filter))
+        .try_init()
+        .map_err(io::Error::other)
+}
+
+/// Directory holding the log files, once logging is initialized.
+pub fn log_dir() -> Option<&'static Path> {
+    LOG_DIR.get().map(PathBuf::as_path)
+}
+
+/// Buffered entries at `level` or more severe, oldest first.
+pub fn recent(level: LogLevel) -> Vec<LogEntry> {
+    RECENT.lock()
+        .expect("lock poisoned")
+        .iter()
+        .filter(|entry| entry.level <= level)
+        .cloned()
+        .collect()
+}
+
+fn publish(entry: LogEntry) {
+    {
+        let mut recent = RECENT.lock().expect("lock poisoned");
+        if recent.len() == RECENT_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry.clone());
+    }
+    if let Some(app) = APP.get() {
+        // Not logged: that would feed back into this stream.
+        let _ = app.emit(LOG_EVENT, entry);
+    }
+}
+
+/// Fields recorded on a span, plus when it started.
+struct SpanData {
+    fields: BTreeMap<String, String>,
+    started: Instant,
+}
+
+/// Layer turning events into [`LogEntry`]s and reporting how long each span ran.
+struct LogStream;
+
+impl<S> Layer<S> for LogStream
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut fields = FieldVisitor::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanData {
+                fields: fields.fields,
+                started: Instant::now(),
+            });
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let mut fields = FieldVisitor::default();
+        values.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+                data.fields.extend(fields.fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let mut fields = BTreeMap::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(data) = span.extensions().get::<SpanData>() {
+                    fields.extend(data.fields.clone());
+                }
+            }
+        }
+        fields.extend(visitor.fields);
+
+        let metadata = event.metadata();
+        publish(LogEntry {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            level: metadata.level().into(),
+            target: metadata.target().to_string(),
+            message: visitor.message.unwrap_or_default(),
+            fields,
+        });
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let extensions = span.extensions();
+        let data = match extensions.get::<SpanData>() {
+            Some(data) => data,
+            None => return,
+        };
+
+        let mut fields = data.fields.clone();
+        fields.insert("elapsed_ms".to_string(), data.started.elapsed().as_millis().to_string());
+        let metadata = span.metadata();
+        publish(LogEntry {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            level: metadata.level().into(),
+            target: metadata.target().to_string(),
+            message: format!("{} finished", span.name()),
+            fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: BTreeMap<String, String>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: String) {
+        let name = field.name();
+        if name == "message" {
+            self.message = Some(value);
+        } else if is_secret(name) {
+            self.fields.insert(name.to_string(), REDACTED.to_string());
+        } else {
+            self.fields.insert(name.to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.insert(field, format!("{:?}", value));
+    }
+}
+
+/// Formats a field for the stdout and file layers, masking secrets like [`FieldVisitor`].
+fn format_field(writer: &mut Writer<'_>, field: &Field, value: &dyn Debug) -> fmt::Result {
+    let name = field.name();
+    if name == "message" {
+        write!(writer, "{:?}", value)
+    } else if is_secret(name) {
+        write!(writer, "{}={}", name, REDACTED)
+    } else {
+        write!(writer, "{}={:?}", name, value)
+    }
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_FIELDS.iter().any(|secret| name.contains(secret))
+}
+
+/// Log file that is rotated once it grows past [`MAX_FILE_BYTES`].
+struct RotatingFile {
+    dir: PathBuf,
+    active: Mutex<ActiveFile>,
+}
+
+struct ActiveFile {
+    /// Closed while rotating, since Windows cannot rename an open file.
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = Self::open_active(dir)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            active: Mutex::new(ActiveFile { file: Some(file), size }),
+        })
+    }
+
+    fn open_active(dir: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(dir.join(LOG_FILE))
+    }
+
+    /// Shifts `elastic-eye.log.N` to `.N+1`, dropping the oldest, and starts a new file.
+    fn rotate(&self, active: &mut ActiveFile) -> io::Result<()> {
+        active.file = None;
+        let rotated = |n: usize| self.dir.join(format!("{}.{}", LOG_FILE, n));
+        let _ = fs::remove_file(rotated(KEEP_FILES));
+        for n in (1..KEEP_FILES).rev() {
+            let _ = fs::rename(rotated(n), rotated(n + 1));
+        }
+        fs::rename(self.dir.join(LOG_FILE), rotated(1))?;
+        active.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for &RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut active = self.active.lock().expect("lock poisoned");
+        if active.size > 0 && active.size + buf.len() as u64 > MAX_FILE_BYTES {
+            self.rotate(&mut active)?;
+        }
+        let file = match &mut active.file {
+            Some(file) => file,
+            None => active.file.insert(RotatingFile::open_active(&self.dir)?),
+        };
+        let written = file.write(buf)?;
+        active.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.active.lock().expect("lock poisoned").file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFile {
+    type Writer = &'a RotatingFile;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self
+    }
+}
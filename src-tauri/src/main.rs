@@ -3,21 +3,22 @@
 
 use elastic_eye_lib::{AppState, commands, db::Database};
 use elastic_eye_lib::elasticsearch::client::IndexDetails;
+use elastic_eye_lib::error::{AppError, AppResult};
+use elastic_eye_lib::i18n::{self, Message};
+use elastic_eye_lib::logging;
 use tauri::Manager;
 use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
-    if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
-    }
-    tracing_subscriber::fmt::init();
-
     let app = tauri::Builder::default()
         .setup(|app| {
             let app_handle = app.handle();
             let app_dir = app_handle.path().app_data_dir().expect("Failed to get app dir");
             std::fs::create_dir_all(&app_dir).expect("Failed to create app dir");
+            if let Err(e) = logging::init(&app_dir.join("logs"), app_handle.clone()) {
+                eprintln!("Failed to initialize logging: {}", e);
+            }
             let db_path = app_dir.join("connections.db");
 
             // 创建一个通道来传递数据库实例
@@ -27,12 +28,28 @@ async fn main() {
             std::thread::spawn(move || {
                 let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
                 let db = rt.block_on(Database::new(db_path)).expect("Failed to initialize database");
-                tx.send(db).expect("Failed to send database instance");
+                let settings = rt.block_on(db.load_settings()).unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Failed to load settings");
+                    Default::default()
+                });
+                i18n::set_locale(settings.locale);
+                let requests = if settings.persist_requests {
+                    rt.block_on(db.load_requests()).unwrap_or_else(|e| {
+                        tracing::warn!(error = %e, "Failed to load request log");
+                        Vec::new()
+                    })
+                } else {
+                    Vec::new()
+                };
+                tx.send((db, settings.persist_requests, requests)).expect("Failed to send database instance");
             });
 
             // 接收数据库实例
-            let db = rx.recv().expect("Failed to receive database instance");
-            app.manage(AppState::new(db));
+            let (db, persist_requests, requests) = rx.recv().expect("Failed to receive database instance");
+            let state = AppState::new(db);
+            state.inspector.set_persist(persist_requests);
+            state.inspector.restore(requests);
+            app.manage(state);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -40,14 +57,36 @@ async fn main() {
             commands::disconnect_elasticsearch,
             commands::list_connections,
             commands::test_connection,
+            commands::get_connection_health,
+            commands::get_server_version,
+            commands::cancel_request,
+            commands::list_requests,
+            commands::clear_requests,
+            commands::get_request_curl,
             commands::get_current_config,
             commands::list_indices,
             commands::get_cluster_info,
             commands::get_shards_info,
             commands::search,
+            commands::open_search,
+            commands::search_page,
+            commands::close_search,
+            commands::start_export,
+            commands::cancel_export,
             commands::save_connection_info,
+            commands::connect_saved_connection,
             commands::load_saved_connections,
+            commands::list_profile_errors,
             commands::delete_saved_connection,
+            commands::export_connections,
+            commands::import_connections,
+            commands::get_settings,
+            commands::update_settings,
+            commands::get_recent_logs,
+            commands::get_log_dir,
+            commands::get_vault_status,
+            commands::unlock_vault,
+            commands::change_master_password,
             commands::list_snapshot_repositories,
             commands::list_snapshots,
             commands::create_snapshot_repository,
@@ -63,15 +102,17 @@ async fn main() {
 
     app.run(|_app_handle, event| match event {
         tauri::RunEvent::Exit => {
-            println!("Application exiting");
+            tracing::info!("Application exiting");
         }
         _ => {}
     });
 }
 
 #[tauri::command]
-async fn get_index_details(connection_id: String, index_name: String, state: tauri::State<'_, AppState>) -> Result<IndexDetails, String> {
-    let es_clients = state.es_clients.lock().await;
-    let client = es_clients.get(&connection_id).ok_or("Connection not found")?;
-    client.get_index_details(&index_name).await.map_err(|e| e.to_string())
+async fn get_index_details(connection_id: String, index_name: String, state: tauri::State<'_, AppState>) -> AppResult<IndexDetails> {
+    let client = state
+        .get_client(&connection_id)
+        .await
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))?;
+    client.get_index_details(&index_name).await
 }
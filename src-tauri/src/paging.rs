@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use crate::{
+    elasticsearch::client::ESClient,
+    error::{AppError, AppResult},
+    i18n::Message,
+    ClientMap,
+};
+
+/// Hits per page when the query sets no `size`.
+pub const DEFAULT_PAGE_SIZE: u64 = 100;
+/// How long an unused session, and its point in time, stays open.
+pub const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(300);
+
+/// One page of a paged search.
+#[derive(Debug, Serialize)]
+pub struct SearchPage {
+    pub session_id: String,
+    /// Opaque cursor of the following page, absent on the last one.
+    pub next_cursor: Option<String>,
+    /// Search response; `hits.total` counts the whole result set.
+    pub response: Value,
+}
+
+struct Session {
+    connection_id: String,
+    /// Latest point in time id; searches may hand back a new one.
+    pit_id: String,
+    /// Query with its `size` and tiebreaking `sort`, without `pit` and `search_after`.
+    body: Value,
+    page_size: u64,
+    keep_alive: Duration,
+    expiry: JoinHandle<()>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    session: String,
+    /// Sort values of the last hit of the previous page.
+    after: Value,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        BASE64.encode(serde_json::to_vec(self).expect("cursor serializes"))
+    }
+
+    fn decode(cursor: &str) -> AppResult<Self> {
+        BASE64
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| AppError::ValidationError(Message::key("search.invalid_cursor")))
+    }
+}
+
+/// Searches paged with `search_after` over a point in time, so results stay
+/// consistent while browsing and are not limited to the first 10,000 hits.
+/// Sessions close their point in time once unused for their keep-alive.
+pub struct SearchSessions {
+    clients: ClientMap,
+    next_id: AtomicU64,
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+}
+
+impl SearchSessions {
+    pub fn new(clients: ClientMap) -> Self {
+        Self {
+            clients,
+            next_id: AtomicU64::new(0),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Opens a point in time on `index` and returns the first page of `body`.
+    pub async fn open(
+        &self,
+        connection_id: &str,
+        index: &str,
+        body: Value,
+        keep_alive: Duration,
+        timeout: Option<Duration>,
+        opaque_id: Option<&str>,
+    ) -> AppResult<SearchPage> {
+        let client = client(&self.clients, connection_id).await?;
+        let tiebreaker = client.server_version()?.pit_tiebreaker();
+        let (body, page_size) = prepare(body, tiebreaker)?;
+        let pit_id = client.open_point_in_time(index, &keep_alive_param(keep_alive)).await?;
+
+        let session_id = format!("search-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        debug!(session_id, index, "Opened search session");
+        let session = Session {
+            connection_id: connection_id.to_string(),
+            pit_id,
+            body,
+            page_size,
+            keep_alive,
+            expiry: self.expire_after(&session_id, keep_alive),
+        };
+        self.sessions.lock().expect("lock poisoned").insert(session_id.clone(), session);
+
+        let page = self.fetch(connection_id, &session_id, None, timeout, opaque_id).await;
+        if page.is_err() {
+            self.close(&session_id).await;
+        }
+        page
+    }
+
+    /// Returns the page `cursor` points to, on `connection_id`.
+    pub async fn page(
+        &self,
+        connection_id: &str,
+        cursor: &str,
+        timeout: Option<Duration>,
+        opaque_id: Option<&str>,
+    ) -> AppResult<SearchPage> {
+        let cursor = Cursor::decode(cursor)?;
+        self.fetch(connection_id, &cursor.session, Some(cursor.after), timeout, opaque_id).await
+    }
+
+    async fn fetch(
+        &self,
+        connection_id: &str,
+        session_id: &str,
+        after: Option<Value>,
+        timeout: Option<Duration>,
+        opaque_id: Option<&str>,
+    ) -> AppResult<SearchPage> {
+        let (mut body, page_size) = {
+            let mut sessions = self.sessions.lock().expect("lock poisoned");
+            let session = sessions
+                .get_mut(session_id)
+                .filter(|session| session.connection_id == connection_id)
+                .ok_or_else(|| AppError::StateError(Message::key("search.session_expired")))?;
+            session.expiry.abort();
+            session.expiry = self.expire_after(session_id, session.keep_alive);
+
+            let mut body = session.body.clone();
+            body["pit"] = json!({
+                "id": session.pit_id,
+                "keep_alive": keep_alive_param(session.keep_alive),
+            });
+            (body, session.page_size)
+        };
+        if let Some(after) = after {
+            body["search_after"] = after;
+        }
+
+        let client = client(&self.clients, connection_id).await?;
+        let response = client.search_point_in_time(body, timeout, opaque_id).await?;
+
+        if let Some(pit_id) = response["pit_id"].as_str() {
+            if let Some(session) = self.sessions.lock().expect("lock poisoned").get_mut(session_id) {
+                session.pit_id = pit_id.to_string();
+            }
+        }
+
+        let hits = response["hits"]["hits"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let next_cursor = match hits.last() {
+            Some(last) if hits.len() as u64 == page_size => Some(
+                Cursor {
+                    session: session_id.to_string(),
+                    after: last["sort"].clone(),
+                }
+                .encode(),
+            ),
+            _ => None,
+        };
+
+        Ok(SearchPage {
+            session_id: session_id.to_string(),
+            next_cursor,
+            response,
+        })
+    }
+
+    /// Ends the session and releases its point in time. Returns whether it was open.
+    pub async fn close(&self, session_id: &str) -> bool {
+        let session = self.sessions.lock().expect("lock poisoned").remove(session_id);
+        match session {
+            Some(session) => {
+                session.expiry.abort();
+                release(&self.clients, session).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ends every session of `connection_id`, e.g. before it disconnects.
+    pub async fn close_connection(&self, connection_id: &str) {
+        let sessions: Vec<Session> = {
+            let mut sessions = self.sessions.lock().expect("lock poisoned");
+            let ids: Vec<String> = sessions
+                .iter()
+                .filter(|(_, session)| session.connection_id == connection_id)
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| sessions.remove(id)).collect()
+        };
+        for session in sessions {
+            session.expiry.abort();
+            release(&self.clients, session).await;
+        }
+    }
+
+    /// Closes `session_id` once `keep_alive` passes without it being used.
+    fn expire_after(&self, session_id: &str, keep_alive: Duration) -> JoinHandle<()> {
+        let sessions = self.sessions.clone();
+        let clients = self.clients.clone();
+        let session_id = session_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(keep_alive).await;
+            let session = sessions.lock().expect("lock poisoned").remove(&session_id);
+            if let Some(session) = session {
+                debug!(session_id, "Search session expired");
+                release(&clients, session).await;
+            }
+        })
+    }
+}
+
+async fn client(clients: &ClientMap, connection_id: &str) -> AppResult<Arc<ESClient>> {
+    clients
+        .read()
+        .await
+        .get(connection_id)
+        .cloned()
+        .ok_or_else(|| AppError::ConnectionError(Message::key("connection.not_connected")))
+}
+
+/// Closes the point in time of `session`. Failures are only logged: the cluster drops
+/// it by itself once its keep-alive lapses.
+async fn release(clients: &ClientMap, session: Session) {
+    let client = match client(clients, &session.connection_id).await {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+    if let Err(e) = client.close_point_in_time(&session.pit_id).await {
+        warn!(error = %e, "Failed to close point in time");
+    }
+}
+
+pub(crate) fn keep_alive_param(keep_alive: Duration) -> String {
+    format!("{}s", keep_alive.as_secs().max(1))
+}
+
+/// Validates a paged query and gives it a `size` and a `sort` ending with `tiebreaker`.
+pub(crate) fn prepare(mut body: Value, tiebreaker: Value) -> AppResult<(Value, u64)> {
+    let query = body
+        .as_object_mut()
+        .ok_or_else(|| AppError::ValidationError(Message::key("search.query_not_object")))?;
+    if query.get("from").and_then(Value::as_u64).unwrap_or(0) > 0 {
+        return Err(AppError::ValidationError(
+            Message::key("search.from_not_allowed")
+        ));
+    }
+    for key in ["from", "pit", "search_after", "scroll"] {
+        query.remove(key);
+    }
+
+    let page_size = match query.get("size") {
+        Some(size) => size
+            .as_u64()
+            .ok_or_else(|| AppError::ValidationError(Message::key("search.invalid_size")))?,
+        None => DEFAULT_PAGE_SIZE,
+    };
+    query.insert("size".to_string(), json!(page_size));
+
+    let mut sort = match query.remove("sort") {
+        Some(Value::Array(sort)) => sort,
+        Some(sort) => vec![sort],
+        None => vec![json!({ "_score": "desc" })],
+    };
+    let tiebreaker_field = tiebreaker.as_object().and_then(|t| t.keys().next()).cloned();
+    let has_tiebreaker = sort.iter().any(|entry| match entry {
+        Value::String(field) => Some(field) == tiebreaker_field.as_ref(),
+        Value::Object(entry) => tiebreaker_field.as_ref().is_some_and(|field| entry.contains_key(field)),
+        _ => false,
+    });
+    if !has_tiebreaker {
+        sort.push(tiebreaker);
+    }
+    query.insert("sort".to_string(), Value::Array(sort));
+    query.insert("track_scores".to_string(), json!(true));
+
+    Ok((body, page_size))
+}
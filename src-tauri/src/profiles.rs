@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use config::{Config, Environment, File};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::{
+    config::ElasticsearchConfig,
+    db::{ConnectionSource, SavedConnection},
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// Prefix of ids given to file and environment profiles, so they never clash with saved ones.
+pub const PROFILE_ID_PREFIX: &str = "profile:";
+const ENV_PREFIX: &str = "ELASTIC_EYE";
+const PROFILE_FILES: &[&str] = &["profiles.toml", "profiles.yaml", "profiles.yml"];
+/// Fields read verbatim from the environment, so secrets such as `password=12345`
+/// are not turned into numbers.
+const STRING_FIELDS: &[&str] = &[
+    "name", "cloud_id", "username", "password", "api_key", "bearer_token",
+    "ca_cert_path", "client_cert_path", "client_cert_password", "ca_fingerprint",
+    "url", "value", "host", "user", "key_path", "key_passphrase",
+];
+
+#[derive(Debug, Deserialize)]
+struct ProfileEntry {
+    name: Option<String>,
+    #[serde(flatten)]
+    config: ElasticsearchConfig,
+}
+
+/// Profiles that could be read, and why the others were skipped.
+#[derive(Debug, Serialize, Default)]
+pub struct Profiles {
+    pub connections: Vec<SavedConnection>,
+    pub errors: Vec<ProfileError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileError {
+    pub id: String,
+    pub error: AppError,
+}
+
+/// `$ELASTIC_EYE_CONFIG_DIR`, else `$XDG_CONFIG_HOME/elastic-eye`, else `~/.config/elastic-eye`.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("ELASTIC_EYE_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(base.join("elastic-eye"))
+}
+
+/// Loads profiles from the profiles file and `ELASTIC_EYE_PROFILES__<ID>__<FIELD>`
+/// variables, environment values taking precedence. `${VAR}` in any string value is
+/// replaced by that environment variable. Invalid profiles are skipped and reported.
+pub fn load_profiles() -> AppResult<Profiles> {
+    let mut builder = Config::builder();
+    if let Some(dir) = config_dir() {
+        for file in PROFILE_FILES {
+            builder = builder.add_source(File::from(dir.join(file)).required(false));
+        }
+    }
+    // Numbers and booleans are parsed from the environment, except in string fields.
+    let (strings, typed): (HashMap<String, String>, HashMap<String, String>) = std::env::vars()
+        .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+        .partition(|(key, _)| {
+            let field = key.rsplit("__").next().unwrap_or_default().to_ascii_lowercase();
+            STRING_FIELDS.contains(&field.as_str())
+        });
+    let environment = |vars| {
+        Environment::with_prefix(ENV_PREFIX)
+            .prefix_separator("_")
+            .separator("__")
+            .source(Some(vars))
+    };
+    let settings = builder
+        .add_source(environment(typed).try_parsing(true))
+        .add_source(environment(strings))
+        .build()
+        .map_err(|e| AppError::ValidationError(Message::key("profile.load_failed").arg("error", e)))?;
+
+    let root: Value = settings
+        .try_deserialize()
+        .map_err(|e| AppError::ValidationError(Message::key("profile.invalid_file").arg("error", e)))?;
+    let profiles = match root.get("profiles").and_then(|p| p.as_object()) {
+        Some(profiles) => profiles.clone(),
+        None => return Ok(Profiles::default()),
+    };
+
+    let mut loaded = Profiles::default();
+    for (id, profile) in profiles.into_iter().collect::<BTreeMap<_, _>>() {
+        match parse_profile(&id, profile) {
+            Ok(connection) => loaded.connections.push(connection),
+            Err(error) => loaded.errors.push(ProfileError {
+                id: format!("{}{}", PROFILE_ID_PREFIX, id),
+                error,
+            }),
+        }
+    }
+    Ok(loaded)
+}
+
+fn parse_profile(id: &str, mut profile: Value) -> AppResult<SavedConnection> {
+    interpolate(&mut profile).map_err(|var| {
+        AppError::ValidationError(Message::key("profile.unset_variable").arg("id", id).arg("variable", var))
+    })?;
+    // Environment variables cannot hold lists, so accept `hosts` as a comma separated string.
+    if let Some(hosts) = profile.get("hosts").and_then(|h| h.as_str()).map(String::from) {
+        profile["hosts"] = hosts.split(',').map(|h| Value::String(h.trim().to_string())).collect();
+    }
+
+    let entry: ProfileEntry = serde_json::from_value(profile)
+        .map_err(|e| AppError::ValidationError(Message::key("profile.invalid").arg("id", id).arg("error", e)))?;
+    entry.config.validate()
+        .map_err(|e| AppError::ValidationError(Message::key("profile.invalid").arg("id", id).arg("error", e)))?;
+
+    Ok(SavedConnection {
+        id: format!("{}{}", PROFILE_ID_PREFIX, id),
+        name: entry.name.unwrap_or_else(|| id.to_string()),
+        config: entry.config,
+        created_at: 0,
+        last_used_at: 0,
+        source: ConnectionSource::Profile,
+    })
+}
+
+pub fn get_profile(id: &str) -> AppResult<SavedConnection> {
+    let mut profiles = load_profiles()?;
+    if let Some(index) = profiles.connections.iter().position(|profile| profile.id == id) {
+        return Ok(profiles.connections.swap_remove(index));
+    }
+    match profiles.errors.into_iter().find(|profile| profile.id == id) {
+        Some(profile) => Err(profile.error),
+        None => Err(AppError::ValidationError(Message::key("profile.not_found").arg("id", id))),
+    }
+}
+
+/// Expands `${VAR}` in every string, returning the name of the first unset variable.
+fn interpolate(value: &mut Value) -> Result<(), String> {
+    match value {
+        Value::String(s) => {
+            *s = expand(s)?;
+            Ok(())
+        }
+        Value::Array(items) => items.iter_mut().try_for_each(interpolate),
+        Value::Object(fields) => fields.values_mut().try_for_each(interpolate),
+        _ => Ok(()),
+    }
+}
+
+fn expand(input: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = match after.find('}') {
+            Some(end) => end,
+            None => {
+                output.push_str(&rest[start..]);
+                return Ok(output);
+            }
+        };
+        let var = &after[..end];
+        output.push_str(&std::env::var(var).map_err(|_| var.to_string())?);
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
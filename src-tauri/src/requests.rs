@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use serde::Serialize;
+use tokio::sync::oneshot;
+use crate::{
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+/// In-flight requests started with a frontend supplied request id.
+#[derive(Default)]
+pub struct RequestRegistry {
+    inflight: Mutex<HashMap<String, InFlight>>,
+}
+
+struct InFlight {
+    connection_id: String,
+    cancel: oneshot::Sender<()>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelOutcome {
+    /// Whether the request was still running when it was cancelled.
+    pub cancelled: bool,
+    /// Server-side tasks stopped through the Tasks API.
+    pub server_tasks_cancelled: usize,
+}
+
+/// Unregisters the request even when the command future is dropped.
+struct Registration<'a> {
+    registry: &'a RequestRegistry,
+    request_id: &'a str,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.registry.inflight.lock().expect("lock poisoned").remove(self.request_id);
+    }
+}
+
+impl RequestRegistry {
+    /// Runs `request`, making it cancellable through [`RequestRegistry::cancel`] when a
+    /// request id is given.
+    pub async fn run<T>(
+        &self,
+        request_id: Option<&str>,
+        connection_id: &str,
+        request: impl Future<Output = AppResult<T>>,
+    ) -> AppResult<T> {
+        let request_id = match request_id {
+            Some(id) => id,
+            None => return request.await,
+        };
+
+        let (cancel, cancelled) = oneshot::channel();
+        {
+            let mut inflight = self.inflight.lock().expect("lock poisoned");
+            if inflight.contains_key(request_id) {
+                return Err(AppError::ValidationError(Message::key("request.already_running").arg("id", request_id)));
+            }
+            inflight.insert(request_id.to_string(), InFlight {
+                connection_id: connection_id.to_string(),
+                cancel,
+            });
+        }
+        let _registration = Registration { registry: self, request_id };
+
+        tokio::select! {
+            result = request => result,
+            _ = cancelled => Err(AppError::CancelledError(Message::key("request.cancelled").arg("id", request_id))),
+        }
+    }
+
+    /// Aborts the request and returns the connection it was running on.
+    pub fn cancel(&self, request_id: &str) -> Option<String> {
+        let inflight = self.inflight.lock().expect("lock poisoned").remove(request_id)?;
+        let _ = inflight.cancel.send(());
+        Some(inflight.connection_id)
+    }
+}
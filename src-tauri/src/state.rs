@@ -1,21 +1,60 @@
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 use std::collections::HashMap;
 use crate::{
-    elasticsearch::client::ESClient,
+    elasticsearch::{client::ESClient, health::HealthMonitor},
     db::Database,
+    export::ExportJobs,
+    inspector::RequestInspector,
+    paging::SearchSessions,
+    requests::RequestRegistry,
 };
 
+/// Connected clients by connection id. The lock is only held to look a client up or
+/// swap it, never across a request, so commands on any connection run concurrently.
+pub type ClientMap = Arc<RwLock<HashMap<String, Arc<ESClient>>>>;
+
 pub struct AppState {
-    pub es_clients: Arc<Mutex<HashMap<String, ESClient>>>,
+    pub es_clients: ClientMap,
     pub db: Database,
+    pub health: HealthMonitor,
+    pub requests: RequestRegistry,
+    pub inspector: Arc<RequestInspector>,
+    pub searches: SearchSessions,
+    pub exports: ExportJobs,
 }
 
 impl AppState {
     pub fn new(db: Database) -> Self {
+        let es_clients: ClientMap = Arc::new(RwLock::new(HashMap::new()));
         Self {
-            es_clients: Arc::new(Mutex::new(HashMap::new())),
+            searches: SearchSessions::new(es_clients.clone()),
+            es_clients,
+            inspector: Arc::new(RequestInspector::new(Some(db.clone()))),
             db,
+            health: HealthMonitor::default(),
+            requests: RequestRegistry::default(),
+            exports: ExportJobs::default(),
         }
     }
-} 
\ No newline at end of file
+
+    pub async fn get_client(&self, connection_id: &str) -> Option<Arc<ESClient>> {
+        self.es_clients.read().await.get(connection_id).cloned()
+    }
+
+    pub async fn clients(&self) -> Vec<Arc<ESClient>> {
+        self.es_clients.read().await.values().cloned().collect()
+    }
+
+    /// Stores `client`, closing the one it replaces.
+    pub async fn insert_client(&self, connection_id: String, client: ESClient) {
+        let previous = self.es_clients.write().await.insert(connection_id, Arc::new(client));
+        if let Some(previous) = previous {
+            previous.close().await;
+        }
+    }
+
+    pub async fn remove_client(&self, connection_id: &str) -> Option<Arc<ESClient>> {
+        self.es_clients.write().await.remove(connection_id)
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::path::Path;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use crate::{
+    crypto::{self, KdfParams, MasterKey},
+    db::{ConnectionSource, Database, SavedConnection},
+    error::{AppError, AppResult},
+    i18n::Message,
+};
+
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+}
+
+impl ExportFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => ExportFormat::Yaml,
+            _ => ExportFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretHandling {
+    /// Drop passwords, API keys and other secrets from the file.
+    Omit,
+    /// Encrypt secrets with a key derived from an export passphrase.
+    Encrypt,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConnectionsFile {
+    version: u32,
+    /// Salt of the passphrase-derived key, present when secrets are encrypted.
+    #[serde(default)]
+    salt: Option<String>,
+    /// Argon2 parameters of that key; files without them used [`KdfParams::LEGACY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kdf: Option<KdfParams>,
+    connections: Vec<SavedConnection>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+}
+
+pub async fn export_connections(
+    db: &Database,
+    ids: &[String],
+    path: &Path,
+    format: Option<ExportFormat>,
+    secrets: SecretHandling,
+    passphrase: Option<String>,
+) -> AppResult<usize> {
+    // Omitted secrets need no key, so a locked store can still export.
+    let saved = match secrets {
+        SecretHandling::Omit => db.list_connections().await?,
+        SecretHandling::Encrypt => db.decrypted_connections().await?,
+    };
+    let mut connections: Vec<SavedConnection> = saved
+        .into_iter()
+        .filter(|connection| ids.is_empty() || ids.contains(&connection.id))
+        .collect();
+
+    let (salt, kdf) = match secrets {
+        SecretHandling::Omit => {
+            for connection in &mut connections {
+                for secret in connection.config.secrets_mut() {
+                    *secret = None;
+                }
+            }
+            (None, None)
+        }
+        SecretHandling::Encrypt => {
+            let passphrase = passphrase
+                .ok_or_else(|| AppError::ValidationError(Message::key("transfer.passphrase_required")))?;
+            let salt = crypto::generate_salt();
+            let kdf = KdfParams::default();
+            let key = MasterKey::derive(&passphrase, &salt, &kdf)?;
+            for connection in &mut connections {
+                for secret in connection.config.secrets_mut().into_iter().flatten() {
+                    *secret = key.encrypt(secret)?;
+                }
+            }
+            (Some(BASE64.encode(salt)), Some(kdf))
+        }
+    };
+
+    let file = ConnectionsFile {
+        version: EXPORT_VERSION,
+        salt,
+        kdf,
+        connections,
+    };
+    let contents = match format.unwrap_or_else(|| ExportFormat::from_path(path)) {
+        ExportFormat::Json => serde_json::to_string_pretty(&file)
+            .map_err(|e| AppError::StateError(Message::key("transfer.serialize_failed").arg("error", e)))?,
+        ExportFormat::Yaml => serde_yaml::to_string(&file)
+            .map_err(|e| AppError::StateError(Message::key("transfer.serialize_failed").arg("error", e)))?,
+    };
+
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(|e| AppError::StateError(Message::key("file.write_failed").arg("path", path.display()).arg("error", e)))?;
+
+    Ok(file.connections.len())
+}
+
+pub async fn import_connections(
+    db: &Database,
+    path: &Path,
+    on_conflict: ConflictStrategy,
+    passphrase: Option<String>,
+) -> AppResult<ImportSummary> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| AppError::ValidationError(Message::key("file.read_failed").arg("path", path.display()).arg("error", e)))?;
+    let file: ConnectionsFile = match ExportFormat::from_path(path) {
+        ExportFormat::Json => serde_json::from_str(&contents)
+            .map_err(|e| AppError::ValidationError(Message::key("transfer.invalid_file").arg("error", e)))?,
+        ExportFormat::Yaml => serde_yaml::from_str(&contents)
+            .map_err(|e| AppError::ValidationError(Message::key("transfer.invalid_file").arg("error", e)))?,
+    };
+    if file.version > EXPORT_VERSION {
+        return Err(AppError::ValidationError(
+            Message::key("transfer.unsupported_version").arg("version", file.version)
+        ));
+    }
+
+    let key = match file.salt {
+        Some(salt) => {
+            let passphrase = passphrase
+                .ok_or_else(|| AppError::ValidationError(Message::key("transfer.encrypted")))?;
+            let salt = BASE64
+                .decode(salt)
+                .map_err(|e| AppError::ValidationError(Message::key("transfer.invalid_file").arg("error", e)))?;
+            Some(MasterKey::derive(&passphrase, &salt, &file.kdf.unwrap_or(KdfParams::LEGACY))?)
+        }
+        None => None,
+    };
+
+    // Decrypt and check every entry before anything is written.
+    let mut connections = file.connections;
+    for connection in &mut connections {
+        connection.source = ConnectionSource::Database;
+        for secret in connection.config.secrets_mut().into_iter().flatten() {
+            if crypto::is_encrypted(secret) {
+                let key = key.as_ref()
+                    .ok_or_else(|| AppError::ValidationError(Message::key("transfer.missing_salt")))?;
+                *secret = key.decrypt(secret)?;
+            }
+        }
+        connection.config.validate().map_err(|e| {
+            AppError::ValidationError(Message::key("transfer.invalid_connection").arg("name", &connection.name).arg("error", e.detail()))
+        })?;
+    }
+
+    // Saved connections plus those imported so far, so duplicates within the file conflict too.
+    let mut known: Vec<(String, String, i64)> = db
+        .list_connections()
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c.name, c.created_at))
+        .collect();
+    let mut ids: HashSet<String> = known.iter().map(|(id, _, _)| id.clone()).collect();
+    let mut names: HashSet<String> = known.iter().map(|(_, name, _)| name.clone()).collect();
+    let mut summary = ImportSummary::default();
+    let mut imported = Vec::with_capacity(connections.len());
+
+    for mut connection in connections {
+        let by_id = known.iter().find(|(id, _, _)| *id == connection.id);
+        let by_name = known.iter().find(|(_, name, _)| *name == connection.name);
+        let conflict = match (by_id, by_name) {
+            (Some(a), Some(b)) if a.0 != b.0 && on_conflict == ConflictStrategy::Overwrite => {
+                // Overwriting either row would leave two connections with the same id or name.
+                return Err(AppError::ValidationError(
+                    Message::key("transfer.ambiguous_overwrite").arg("name", &connection.name)
+                ));
+            }
+            (Some((id, _, created_at)), _) | (None, Some((id, _, created_at))) => Some((id.clone(), *created_at)),
+            (None, None) => None,
+        };
+        match (conflict, on_conflict) {
+            (None, _) => summary.imported += 1,
+            (Some(_), ConflictStrategy::Skip) => {
+                summary.skipped += 1;
+                continue;
+            }
+            (Some((id, created_at)), ConflictStrategy::Overwrite) => {
+                connection.id = id;
+                connection.created_at = created_at;
+                summary.overwritten += 1;
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                connection.id = unique(&connection.id, &ids, |base, n| format!("{}-{}", base, n));
+                connection.name = unique(&connection.name, &names, |base, n| format!("{} ({})", base, n));
+                summary.renamed += 1;
+            }
+        }
+
+        ids.insert(connection.id.clone());
+        names.insert(connection.name.clone());
+        known.push((connection.id.clone(), connection.name.clone(), connection.created_at));
+        imported.push(connection);
+    }
+
+    db.save_connections(imported).await?;
+    Ok(summary)
+}
+
+fn unique(base: &str, taken: &HashSet<String>, candidate: impl Fn(&str, usize) -> String) -> String {
+    (2..)
+        .map(|n| candidate(base, n))
+        .find(|value| !taken.contains(value))
+        .unwrap_or_else(|| base.to_string())
+}
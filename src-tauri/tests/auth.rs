@@ -0,0 +1,92 @@
+//! Authentication modes: which combinations validate and what reaches the server.
+
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use common::MockServer;
+use elastic_eye_lib::{
+    config::ElasticsearchConfig,
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    error::AppError,
+};
+
+fn config() -> ElasticsearchConfig {
+    ElasticsearchConfig {
+        hosts: vec!["http://localhost:9200".to_string()],
+        ..Default::default()
+    }
+}
+
+fn rejected(config: ElasticsearchConfig) -> Option<&'static str> {
+    match config.validate() {
+        Err(AppError::ValidationError(message)) => message.key_name(),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn conflicting_auth_modes_are_rejected() {
+    let basic = || ElasticsearchConfig {
+        username: Some("elastic".to_string()),
+        password: Some("changeme".to_string()),
+        ..config()
+    };
+
+    for conflicting in [
+        ElasticsearchConfig { api_key: Some("id:key".to_string()), ..basic() },
+        ElasticsearchConfig { bearer_token: Some("token".to_string()), ..basic() },
+        ElasticsearchConfig { client_cert_path: Some("client.p12".to_string()), ..basic() },
+        ElasticsearchConfig {
+            api_key: Some("id:key".to_string()),
+            bearer_token: Some("token".to_string()),
+            ..config()
+        },
+    ] {
+        assert_eq!(rejected(conflicting), Some("config.multiple_auth"));
+    }
+
+    let password_only = ElasticsearchConfig { password: Some("changeme".to_string()), ..config() };
+    assert_eq!(rejected(password_only), Some("config.password_without_user"));
+
+    for api_key in ["id:", ":key"] {
+        let config = ElasticsearchConfig { api_key: Some(api_key.to_string()), ..config() };
+        assert_eq!(rejected(config), Some("config.invalid_api_key"));
+    }
+}
+
+#[test]
+fn blank_fields_do_not_count_as_a_mode() {
+    let config = ElasticsearchConfig {
+        username: Some("elastic".to_string()),
+        api_key: Some("  ".to_string()),
+        bearer_token: Some(String::new()),
+        ..config()
+    };
+    config.validate().unwrap();
+}
+
+#[tokio::test]
+async fn each_mode_sends_its_authorization_header() {
+    let server = MockServer::start(Vec::new()).await;
+    let encoded = BASE64.encode("id:key");
+
+    for (config, expected) in [
+        (
+            ElasticsearchConfig { username: Some("elastic".to_string()), password: Some("changeme".to_string()), ..server.config() },
+            format!("Basic {}", BASE64.encode("elastic:changeme")),
+        ),
+        (ElasticsearchConfig { api_key: Some("id:key".to_string()), ..server.config() }, format!("ApiKey {}", encoded)),
+        (ElasticsearchConfig { api_key: Some(encoded.clone()), ..server.config() }, format!("ApiKey {}", encoded)),
+        (ElasticsearchConfig { bearer_token: Some("token".to_string()), ..server.config() }, "Bearer token".to_string()),
+    ] {
+        let info = ConnectionInfo {
+            id: "auth".to_string(),
+            name: "auth".to_string(),
+            hosts: config.hosts.clone(),
+            is_active: false,
+        };
+        let client = ESClient::new(config, info).await.unwrap();
+        client.get_cluster_health().await.unwrap();
+        assert_eq!(server.header("GET /_cluster/health", "authorization"), Some(expected));
+    }
+}
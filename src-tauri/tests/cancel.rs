@@ -0,0 +1,80 @@
+//! Cancelling in-flight requests, locally and on the server.
+
+mod common;
+
+use std::time::Duration;
+use common::{route, MockServer};
+use elastic_eye_lib::{error::AppError, requests::RequestRegistry};
+use serde_json::json;
+
+async fn pending() -> Result<(), AppError> {
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    Ok(())
+}
+
+fn key_name(result: Result<(), AppError>) -> Option<&'static str> {
+    match result {
+        Err(AppError::CancelledError(message)) | Err(AppError::ValidationError(message)) => message.key_name(),
+        other => panic!("expected an error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn cancelling_a_request_stops_it_and_frees_its_id() {
+    let registry = RequestRegistry::default();
+    assert_eq!(registry.run(None, "prod", async { Ok(1) }).await.unwrap(), 1);
+    assert_eq!(registry.cancel("search-1"), None);
+
+    let running = registry.run(Some("search-1"), "prod", pending());
+    let cancel = async {
+        tokio::task::yield_now().await;
+        assert_eq!(key_name(registry.run(Some("search-1"), "prod", pending()).await), Some("request.already_running"));
+        registry.cancel("search-1")
+    };
+    let (result, connection) = tokio::join!(running, cancel);
+    assert_eq!(key_name(result), Some("request.cancelled"));
+    assert_eq!(connection.as_deref(), Some("prod"));
+
+    assert_eq!(registry.cancel("search-1"), None);
+    registry.run(Some("search-1"), "prod", async { Ok(()) }).await.unwrap();
+}
+
+#[tokio::test]
+async fn dropped_requests_are_unregistered() {
+    let registry = RequestRegistry::default();
+    let timed_out = tokio::time::timeout(Duration::from_millis(10), registry.run(Some("search-1"), "prod", pending())).await;
+    assert!(timed_out.is_err());
+    assert_eq!(registry.cancel("search-1"), None);
+}
+
+#[tokio::test]
+async fn only_matching_top_level_tasks_are_cancelled_on_the_server() {
+    let task = |node: &str, id: i64, opaque_id: &str, cancellable: bool, parent: Option<&str>| {
+        json!({
+            "node": node,
+            "id": id,
+            "cancellable": cancellable,
+            "parent_task_id": parent,
+            "headers": { "X-Opaque-Id": opaque_id },
+        })
+    };
+    let server = MockServer::start(vec![
+        route("GET", "/_tasks", 200, json!({
+            "tasks": [
+                task("node-1", 42, "search-1", true, None),
+                task("node-2", 7, "search-1", true, Some("node-1:42")),
+                task("node-1", 43, "search-1", false, None),
+                task("node-1", 44, "search-2", true, None),
+            ]
+        })),
+        route("POST", "/_tasks/node-1:42/_cancel", 200, json!({})),
+    ])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    assert_eq!(client.cancel_tasks("search-1").await.unwrap(), 1);
+    assert_eq!(server.count("POST /_tasks/node-1:42/_cancel"), 1);
+    assert_eq!(server.count("POST /_tasks/node-2:7/_cancel"), 0);
+    assert_eq!(server.count("POST /_tasks/node-1:43/_cancel"), 0);
+    assert_eq!(server.count("POST /_tasks/node-1:44/_cancel"), 0);
+}
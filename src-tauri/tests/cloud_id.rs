@@ -0,0 +1,68 @@
+//! Elastic Cloud deployments addressed by Cloud ID.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use elastic_eye_lib::{
+    config::{ElasticsearchConfig, PoolConfig},
+    error::AppError,
+};
+
+fn cloud(decoded: &str) -> ElasticsearchConfig {
+    ElasticsearchConfig {
+        hosts: Vec::new(),
+        cloud_id: Some(format!("my-deployment:{}", BASE64.encode(decoded))),
+        api_key: Some("id:key".to_string()),
+        ..Default::default()
+    }
+}
+
+fn rejected(config: ElasticsearchConfig) -> Option<&'static str> {
+    match config.validate() {
+        Err(AppError::ValidationError(message)) => message.key_name(),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn cloud_id_decodes_to_the_elasticsearch_endpoint() {
+    let config = cloud("eu-west-1.aws.found.io$abc123$def456");
+    config.validate().unwrap();
+    let urls = config.node_urls().unwrap();
+    assert_eq!(urls.len(), 1);
+    assert_eq!(urls[0].scheme(), "https");
+    assert_eq!(urls[0].host_str(), Some("abc123.eu-west-1.aws.found.io"));
+
+    let with_port = cloud("eu-west-1.aws.found.io:9243$abc123$def456").node_urls().unwrap();
+    assert_eq!(with_port[0].host_str(), Some("abc123.eu-west-1.aws.found.io"));
+    assert_eq!(with_port[0].port(), Some(9243));
+}
+
+#[test]
+fn cloud_id_takes_the_place_of_hosts() {
+    let config = ElasticsearchConfig {
+        hosts: vec!["http://localhost:9200".to_string()],
+        ..cloud("eu-west-1.aws.found.io$abc123$def456")
+    };
+    let urls = config.node_urls().unwrap();
+    assert_eq!(urls.len(), 1);
+    assert_eq!(urls[0].host_str(), Some("abc123.eu-west-1.aws.found.io"));
+}
+
+#[test]
+fn invalid_cloud_ids_are_rejected() {
+    for cloud_id in ["no-separator", "name:not base64!", &format!("name:{}", BASE64.encode("host-only"))] {
+        let config = ElasticsearchConfig { cloud_id: Some(cloud_id.to_string()), ..cloud("") };
+        assert_eq!(rejected(config), Some("config.invalid_cloud_id"));
+    }
+}
+
+#[test]
+fn cloud_connections_need_credentials_and_no_sniffing() {
+    let anonymous = ElasticsearchConfig { api_key: None, ..cloud("eu-west-1.aws.found.io$abc123$def456") };
+    assert_eq!(rejected(anonymous), Some("config.cloud_credentials_required"));
+
+    let sniffing = ElasticsearchConfig {
+        pool: PoolConfig { sniff: true, ..Default::default() },
+        ..cloud("eu-west-1.aws.found.io$abc123$def456")
+    };
+    assert_eq!(rejected(sniffing), Some("config.sniff_cloud"));
+}
@@ -0,0 +1,184 @@
+//! Minimal mock Elasticsearch shared by the integration tests.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+use elastic_eye_lib::{
+    config::{ElasticsearchConfig, RetryPolicy},
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    error::AppError,
+    inspector::RequestInspector,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+const ROOT: &str = r#"{"name":"mock","version":{"number":"8.11.0","build_flavor":"default"},"tagline":"You Know, for Search"}"#;
+
+pub struct Route {
+    pub method: &'static str,
+    pub path: &'static str,
+    /// 0 closes the connection without answering.
+    pub status: u16,
+    pub body: String,
+}
+
+/// Answers each request with a route matching its method and path (query ignored),
+/// or 200 `{}`. Several routes for one request answer in turn, the last one repeating.
+/// The root endpoint is always served so clients can connect.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+struct Request {
+    /// `METHOD /path`.
+    line: String,
+    /// Names lowercased.
+    headers: Vec<(String, String)>,
+}
+
+impl MockServer {
+    pub async fn start(routes: Vec<Route>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let routes = Arc::new(routes);
+        let requests: Arc<Mutex<Vec<Request>>> = Arc::default();
+
+        let log = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(_) => break,
+                };
+                let routes = routes.clone();
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(stream);
+                    loop {
+                        let mut request_line = String::new();
+                        if reader.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let mut parts = request_line.split_whitespace();
+                        let method = parts.next().unwrap_or_default().to_string();
+                        let target = parts.next().unwrap_or_default().to_string();
+                        let path = target.split('?').next().unwrap_or_default().to_string();
+
+                        let mut content_length = 0;
+                        let mut headers = Vec::new();
+                        loop {
+                            let mut line = String::new();
+                            reader.read_line(&mut line).await.unwrap();
+                            let line = line.trim_end();
+                            if line.is_empty() {
+                                break;
+                            }
+                            if let Some((name, value)) = line.split_once(':') {
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    content_length = value.trim().parse().unwrap();
+                                }
+                                headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+                            }
+                        }
+                        let mut body = vec![0; content_length];
+                        reader.read_exact(&mut body).await.unwrap();
+                        let request = format!("{} {}", method, path);
+                        let seen = {
+                            let mut log = log.lock().unwrap();
+                            let seen = log.iter().filter(|r| r.line == request).count();
+                            log.push(Request { line: request, headers });
+                            seen
+                        };
+
+                        let matching: Vec<&Route> = routes.iter().filter(|r| r.method == method && r.path == path).collect();
+                        let (status, body) = match matching.get(seen.min(matching.len().saturating_sub(1))) {
+                            Some(route) => (route.status, route.body.clone()),
+                            None if path == "/" => (200, ROOT.to_string()),
+                            None => (200, "{}".to_string()),
+                        };
+                        if status == 0 {
+                            return;
+                        }
+                        let head = format!(
+                            "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nx-elastic-product: Elasticsearch\r\n\r\n",
+                            status,
+                            body.len()
+                        );
+                        let stream = reader.get_mut();
+                        stream.write_all(head.as_bytes()).await.unwrap();
+                        if method != "HEAD" {
+                            stream.write_all(body.as_bytes()).await.unwrap();
+                        }
+                    }
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn count(&self, request: &str) -> usize {
+        self.requests.lock().unwrap().iter().filter(|r| r.line == request).count()
+    }
+
+    /// Value of header `name` on the latest `request`, e.g. `"GET /_cluster/health"`.
+    pub fn header(&self, request: &str, name: &str) -> Option<String> {
+        let requests = self.requests.lock().unwrap();
+        requests
+            .iter()
+            .rev()
+            .find(|r| r.line == request)?
+            .headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn config(&self) -> ElasticsearchConfig {
+        ElasticsearchConfig {
+            hosts: vec![self.url.clone()],
+            timeout_secs: Some(5),
+            retry: RetryPolicy {
+                max_retries: 2,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 1,
+                retry_on_status: vec![503],
+            },
+            ..Default::default()
+        }
+    }
+
+    pub async fn connect(&self) -> Result<ESClient, AppError> {
+        ESClient::new(self.config(), connection_info(&self.config())).await
+    }
+
+    pub async fn connect_with_inspector(&self, inspector: Arc<RequestInspector>) -> Result<ESClient, AppError> {
+        ESClient::with_inspector(self.config(), connection_info(&self.config()), inspector).await
+    }
+}
+
+fn connection_info(config: &ElasticsearchConfig) -> ConnectionInfo {
+    ConnectionInfo {
+        id: "mock".to_string(),
+        name: "mock".to_string(),
+        hosts: config.hosts.clone(),
+        is_active: false,
+    }
+}
+
+pub fn route(method: &'static str, path: &'static str, status: u16, body: Value) -> Route {
+    Route { method, path, status, body: body.to_string() }
+}
+
+pub fn es_error(kind: &str, reason: &str) -> Value {
+    json!({
+        "error": {
+            "root_cause": [{ "type": kind, "reason": reason }],
+            "type": kind,
+            "reason": reason,
+        },
+    })
+}
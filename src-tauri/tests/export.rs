@@ -0,0 +1,222 @@
+//! Streaming search results to files against a mock Elasticsearch.
+
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use common::{route, MockServer, Route};
+use elastic_eye_lib::{
+    elasticsearch::client::ESClient,
+    error::AppError,
+    export::{export, ExportRequest, ResultFormat, BATCH_SIZE},
+    inspector::{RequestFilter, RequestInspector},
+};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+fn page(hits: Value) -> Value {
+    json!({
+        "pit_id": "pit-2",
+        "_scroll_id": "scroll-1",
+        "hits": { "total": { "value": 2, "relation": "eq" }, "hits": hits },
+    })
+}
+
+fn documents() -> Value {
+    json!([
+        {
+            "_index": "logs", "_id": "1", "_score": null, "sort": [1],
+            "_source": { "message": "hello, world", "host": { "name": "a", "ip": "10.0.0.1" } },
+        },
+        {
+            "_index": "logs", "_id": "2", "_score": null, "sort": [2],
+            "_source": { "message": "say \"hi\"", "tags": ["x", "y"], "host": { "name": "b" } },
+        },
+    ])
+}
+
+async fn setup(routes: Vec<Route>) -> (MockServer, ESClient, Arc<RequestInspector>) {
+    let server = MockServer::start(routes).await;
+    let inspector = Arc::new(RequestInspector::default());
+    let client = server.connect_with_inspector(inspector.clone()).await.unwrap();
+    (server, client, inspector)
+}
+
+fn request(name: &str, format: ResultFormat, columns: Option<Vec<&str>>) -> ExportRequest {
+    let path = std::env::temp_dir().join(format!("elastic-eye-export-{}-{}", std::process::id(), name));
+    ExportRequest {
+        index: "logs".to_string(),
+        query: json!({ "size": 10, "query": { "match_all": {} } }),
+        format,
+        path,
+        columns: columns.map(|columns| columns.into_iter().map(String::from).collect()),
+    }
+}
+
+fn bodies(inspector: &RequestInspector, path: &str) -> Vec<Value> {
+    let filter = RequestFilter { path: Some(path.to_string()), ..Default::default() };
+    inspector
+        .list("mock", &filter)
+        .into_iter()
+        .rev()
+        .filter_map(|record| serde_json::from_str(record.body.as_deref()?).ok())
+        .collect()
+}
+
+fn read(path: &PathBuf) -> String {
+    let contents = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    contents
+}
+
+#[tokio::test]
+async fn writes_ndjson_over_a_point_in_time() {
+    let (server, client, inspector) = setup(vec![
+        route("POST", "/logs/_pit", 200, json!({ "id": "pit-1" })),
+        route("POST", "/_search", 200, page(documents())),
+    ])
+    .await;
+    let request = request("ndjson", ResultFormat::Ndjson, None);
+    let (_cancel, cancelled) = oneshot::channel();
+    let mut updates = Vec::new();
+
+    let summary = export(&client, &request, cancelled, |written, total| updates.push((written, total)))
+        .await
+        .unwrap();
+
+    assert_eq!(summary.written, 2);
+    assert_eq!(updates, vec![(2, Some(2))]);
+    let lines: Vec<Value> = read(&request.path).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines, vec![
+        json!({ "_index": "logs", "_id": "1", "message": "hello, world", "host": { "name": "a", "ip": "10.0.0.1" } }),
+        json!({ "_index": "logs", "_id": "2", "message": "say \"hi\"", "tags": ["x", "y"], "host": { "name": "b" } }),
+    ]);
+
+    // A short batch ends the export without asking for another one.
+    let searches = bodies(&inspector, "/_search");
+    assert_eq!(searches.len(), 1);
+    assert_eq!(searches[0]["size"], BATCH_SIZE);
+    assert_eq!(searches[0]["pit"]["id"], "pit-1");
+    assert_eq!(searches[0]["track_total_hits"], true);
+    assert_eq!(searches[0]["sort"], json!([{ "_score": "desc" }, { "_shard_doc": "asc" }]));
+    assert_eq!(server.count("DELETE /_pit"), 1);
+}
+
+#[tokio::test]
+async fn writes_a_json_array() {
+    let (_server, client, _) = setup(vec![
+        route("POST", "/logs/_pit", 200, json!({ "id": "pit-1" })),
+        route("POST", "/_search", 200, page(documents())),
+    ])
+    .await;
+    let request = request("json", ResultFormat::Json, Some(vec!["_id", "host.name"]));
+    let (_cancel, cancelled) = oneshot::channel();
+
+    export(&client, &request, cancelled, |_, _| {}).await.unwrap();
+
+    // The mock ignores `_source` filtering, so the whole source comes back.
+    let documents: Value = serde_json::from_str(&read(&request.path)).unwrap();
+    assert_eq!(documents.as_array().unwrap().len(), 2);
+    assert_eq!(documents[0]["_id"], "1");
+    assert!(documents[0].get("_index").is_none());
+}
+
+#[tokio::test]
+async fn writes_csv_with_flattened_columns() {
+    let (_server, client, inspector) = setup(vec![
+        route("POST", "/logs/_pit", 200, json!({ "id": "pit-1" })),
+        route("POST", "/_search", 200, page(documents())),
+    ])
+    .await;
+
+    let all = request("all.csv", ResultFormat::Csv, None);
+    let (_cancel, cancelled) = oneshot::channel();
+    export(&client, &all, cancelled, |_, _| {}).await.unwrap();
+    assert_eq!(
+        read(&all.path),
+        "\u{feff}_index,_id,host.ip,host.name,message,tags\r\n\
+         logs,1,10.0.0.1,a,\"hello, world\",\r\n\
+         logs,2,,b,\"say \"\"hi\"\"\",\"[\"\"x\"\",\"\"y\"\"]\"\r\n"
+    );
+
+    let selected = request("selected.csv", ResultFormat::Csv, Some(vec!["_id", "host.name", "host", "missing"]));
+    let (_cancel, cancelled) = oneshot::channel();
+    export(&client, &selected, cancelled, |_, _| {}).await.unwrap();
+    assert_eq!(
+        read(&selected.path),
+        "\u{feff}_id,host.name,host,missing\r\n\
+         1,a,\"{\"\"ip\"\":\"\"10.0.0.1\"\",\"\"name\"\":\"\"a\"\"}\",\r\n\
+         2,b,\"{\"\"name\"\":\"\"b\"\"}\",\r\n"
+    );
+    assert_eq!(bodies(&inspector, "/_search")[1]["_source"], json!(["host.name", "host", "missing"]));
+}
+
+#[tokio::test]
+async fn falls_back_to_scroll_without_point_in_time() {
+    let (server, client, inspector) = setup(vec![
+        route("GET", "/", 200, json!({ "name": "old", "version": { "number": "7.9.3", "build_flavor": "default" } })),
+        route("POST", "/logs/_search", 200, page(documents())),
+        route("POST", "/_search/scroll", 200, page(json!([]))),
+    ])
+    .await;
+    let request = request("scroll", ResultFormat::Ndjson, None);
+    let (_cancel, cancelled) = oneshot::channel();
+
+    let summary = export(&client, &request, cancelled, |_, _| {}).await.unwrap();
+
+    assert_eq!(summary.written, 2);
+    assert_eq!(read(&request.path).lines().count(), 2);
+    assert_eq!(server.count("POST /logs/_pit"), 0);
+    assert_eq!(bodies(&inspector, "/_search/scroll")[0], json!({ "scroll": "300s", "scroll_id": "scroll-1" }));
+    assert_eq!(server.count("DELETE /_search/scroll"), 1);
+}
+
+#[tokio::test]
+async fn reports_csv_fields_missing_from_the_first_batch() {
+    let later = json!([
+        { "_index": "logs", "_id": "3", "_source": { "message": "late", "user": { "name": "c" } } },
+    ]);
+    let (_server, client, _) = setup(vec![
+        route("GET", "/", 200, json!({ "name": "old", "version": { "number": "7.9.3", "build_flavor": "default" } })),
+        route("POST", "/logs/_search", 200, page(documents())),
+        route("POST", "/_search/scroll", 200, page(later)),
+        route("POST", "/_search/scroll", 200, page(json!([]))),
+    ])
+    .await;
+    let request = request("dropped.csv", ResultFormat::Csv, None);
+    let (_cancel, cancelled) = oneshot::channel();
+
+    let summary = export(&client, &request, cancelled, |_, _| {}).await.unwrap();
+
+    assert_eq!(summary.written, 3);
+    assert_eq!(summary.dropped_columns, vec!["user.name"]);
+    assert!(read(&request.path).ends_with("logs,3,,,late,\r\n"));
+}
+
+#[tokio::test]
+async fn cancelling_removes_the_partial_file() {
+    let full: Vec<Value> = (0..BATCH_SIZE)
+        .map(|i| json!({ "_index": "logs", "_id": i.to_string(), "_source": {}, "sort": [i] }))
+        .collect();
+    let (server, client, _) = setup(vec![
+        route("POST", "/logs/_pit", 200, json!({ "id": "pit-1" })),
+        route("POST", "/_search", 200, page(Value::Array(full))),
+    ])
+    .await;
+    let request = request("cancelled", ResultFormat::Ndjson, None);
+    let (cancel, cancelled) = oneshot::channel();
+    let mut cancel = Some(cancel);
+
+    // Every batch is full, so only cancelling ends the export.
+    let result = export(&client, &request, cancelled, |_, _| {
+        if let Some(cancel) = cancel.take() {
+            cancel.send(()).unwrap();
+        }
+    })
+    .await;
+
+    assert!(matches!(result, Err(AppError::CancelledError(_))));
+    assert!(!request.path.exists());
+    assert!(!PathBuf::from(format!("{}.part", request.path.display())).exists());
+    assert_eq!(server.count("DELETE /_pit"), 1);
+}
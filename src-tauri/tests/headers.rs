@@ -0,0 +1,88 @@
+//! Custom headers sent with every request.
+
+mod common;
+
+use std::collections::BTreeMap;
+use common::MockServer;
+use elastic_eye_lib::{
+    config::{CustomHeader, ElasticsearchConfig},
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    error::AppError,
+};
+
+fn headers(entries: &[(&str, &str, bool)]) -> BTreeMap<String, CustomHeader> {
+    entries
+        .iter()
+        .map(|(name, value, secret)| (name.to_string(), CustomHeader { value: Some(value.to_string()), secret: *secret }))
+        .collect()
+}
+
+fn rejected(config: ElasticsearchConfig) -> Option<&'static str> {
+    match config.validate() {
+        Err(AppError::ValidationError(message)) => message.key_name(),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn custom_headers_reach_the_server_on_every_request() {
+    let server = MockServer::start(Vec::new()).await;
+    let config = ElasticsearchConfig {
+        headers: headers(&[("X-Tenant", "blue", false), ("Authorization", "Negotiate abc", false)]),
+        ..server.config()
+    };
+    config.validate().unwrap();
+    let info = ConnectionInfo {
+        id: "headers".to_string(),
+        name: "headers".to_string(),
+        hosts: config.hosts.clone(),
+        is_active: false,
+    };
+
+    let client = ESClient::new(config, info).await.unwrap();
+    client.get_cluster_health().await.unwrap();
+    client.list_snapshot_repositories().await.unwrap();
+    for request in ["GET /_cluster/health", "GET /_snapshot"] {
+        assert_eq!(server.header(request, "x-tenant").as_deref(), Some("blue"));
+        assert_eq!(server.header(request, "authorization").as_deref(), Some("Negotiate abc"));
+    }
+}
+
+#[test]
+fn headers_that_would_be_overridden_or_malformed_are_rejected() {
+    let config = |entries: &[(&str, &str, bool)]| ElasticsearchConfig {
+        hosts: vec!["http://localhost:9200".to_string()],
+        headers: headers(entries),
+        ..Default::default()
+    };
+
+    assert_eq!(rejected(config(&[("Content-Type", "text/plain", false)])), Some("config.managed_header"));
+    assert_eq!(rejected(config(&[("user-agent", "curl", false)])), Some("config.managed_header"));
+    assert_eq!(rejected(config(&[("X Tenant", "blue", false)])), Some("config.invalid_header_name"));
+    assert_eq!(rejected(config(&[("X-Tenant", "blue\nX-Other: 1", false)])), Some("config.invalid_header_value"));
+
+    let with_basic_auth = ElasticsearchConfig {
+        username: Some("elastic".to_string()),
+        password: Some("changeme".to_string()),
+        ..config(&[("Authorization", "Bearer token", false)])
+    };
+    assert_eq!(rejected(with_basic_auth), Some("config.authorization_conflict"));
+}
+
+#[test]
+fn secret_headers_are_sensitive_and_redacted() {
+    let config = ElasticsearchConfig {
+        headers: headers(&[("X-Tenant", "blue", false), ("X-Token", "t0k3n", true), ("Cookie", "session=1", false)]),
+        ..Default::default()
+    };
+
+    let map = config.header_map().unwrap();
+    assert!(!map["x-tenant"].is_sensitive());
+    assert!(map["x-token"].is_sensitive());
+    assert!(map["cookie"].is_sensitive());
+
+    let redacted = config.redacted();
+    assert_eq!(redacted.headers["X-Tenant"].value.as_deref(), Some("blue"));
+    assert_eq!(redacted.headers["X-Token"].value.as_deref(), Some("********"));
+    assert_eq!(redacted.headers["Cookie"].value.as_deref(), Some("********"));
+}
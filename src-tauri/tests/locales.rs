@@ -0,0 +1,55 @@
+//! Message catalogs stay in sync with each other and with the keys the code uses.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+fn catalog(name: &str) -> BTreeMap<String, String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("locales").join(name);
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn placeholders(template: &str) -> BTreeSet<&str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .collect()
+}
+
+/// Every `"prefix.name"` literal passed to `Message::key` or `ensure_writable`.
+fn used_keys(dir: &Path, keys: &mut BTreeSet<String>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            used_keys(&path, keys);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            let source = std::fs::read_to_string(&path).unwrap();
+            for marker in ["Message::key(\"", "ensure_writable(&client, \""] {
+                for part in source.split(marker).skip(1) {
+                    keys.insert(part.split('"').next().unwrap().to_string());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn catalogs_have_the_same_keys_and_placeholders() {
+    let en = catalog("en.json");
+    let zh = catalog("zh-CN.json");
+
+    assert_eq!(en.keys().collect::<Vec<_>>(), zh.keys().collect::<Vec<_>>());
+    for (key, template) in &en {
+        assert_eq!(placeholders(template), placeholders(&zh[key]), "{}", key);
+    }
+}
+
+#[test]
+fn every_used_key_is_translated() {
+    let en = catalog("en.json");
+    let mut keys = BTreeSet::new();
+    used_keys(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut keys);
+
+    let missing: Vec<&String> = keys.iter().filter(|key| !en.contains_key(*key)).collect();
+    assert!(missing.is_empty(), "{:?}", missing);
+}
@@ -0,0 +1,85 @@
+//! Schema migrations of the local database.
+
+use std::path::PathBuf;
+use elastic_eye_lib::{db::Database, error::AppError};
+use rusqlite::Connection;
+
+fn database_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("elastic-eye-{}-{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn versions(conn: &Connection) -> Vec<i64> {
+    let mut stmt = conn.prepare("SELECT version FROM schema_version ORDER BY applied_at, version").unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+}
+
+#[tokio::test]
+async fn new_database_applies_every_migration_without_a_backup() {
+    let path = database_path("fresh");
+    Database::new(path.clone()).await.unwrap();
+
+    let applied = versions(&Connection::open(&path).unwrap());
+    assert_eq!(applied, (1..=applied.len() as i64).collect::<Vec<_>>());
+    assert!(!path.with_extension("db.v0.bak").exists());
+
+    // Opening again is a no-op.
+    Database::new(path.clone()).await.unwrap();
+    assert_eq!(versions(&Connection::open(&path).unwrap()), applied);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn outdated_database_is_backed_up_then_migrated_in_order() {
+    let path = database_path("outdated");
+    let backup = path.with_extension("db.v1.bak");
+    let _ = std::fs::remove_file(&backup);
+    {
+        // A database as the first release left it.
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, description TEXT NOT NULL, applied_at INTEGER NOT NULL);
+             INSERT INTO schema_version VALUES (1, 'create connections table', 0);
+             CREATE TABLE connections (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL, host TEXT NOT NULL, port INTEGER NOT NULL,
+                username TEXT, password TEXT, created_at INTEGER NOT NULL, last_used_at INTEGER NOT NULL
+             );
+             INSERT INTO connections VALUES ('prod', 'Production', 'localhost', 9200, NULL, NULL, 1, 1);",
+        )
+        .unwrap();
+    }
+
+    let db = Database::new(path.clone()).await.unwrap();
+    let applied = versions(&Connection::open(&path).unwrap());
+    assert_eq!(applied, (1..=applied.len() as i64).collect::<Vec<_>>());
+    assert!(applied.len() > 1);
+    let listed = db.list_connections().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].config.hosts, ["http://localhost:9200"]);
+
+    // The backup is the database exactly as it was before migrating.
+    let saved = Connection::open(&backup).unwrap();
+    assert_eq!(versions(&saved), [1]);
+    let columns: i64 = saved
+        .query_row("SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'config'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(columns, 0);
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&backup);
+}
+
+#[tokio::test]
+async fn database_from_a_newer_release_is_refused() {
+    let path = database_path("newer");
+    Database::new(path.clone()).await.unwrap();
+    Connection::open(&path)
+        .unwrap()
+        .execute("INSERT INTO schema_version VALUES (1000, 'from the future', 0)", [])
+        .unwrap();
+
+    let result = Database::new(path.clone()).await;
+    assert!(matches!(result, Err(AppError::StateError(message)) if message.key_name() == Some("db.schema_too_new")));
+    let _ = std::fs::remove_file(&path);
+}
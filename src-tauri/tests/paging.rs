@@ -0,0 +1,117 @@
+//! Point in time paging against a mock Elasticsearch.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use common::{route, MockServer};
+use elastic_eye_lib::{
+    error::AppError,
+    inspector::{RequestFilter, RequestInspector},
+    paging::SearchSessions,
+    ClientMap,
+};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+fn hits(count: usize) -> Value {
+    let hits: Vec<Value> = (0..count)
+        .map(|i| json!({ "_id": i.to_string(), "_source": {}, "sort": [i, i] }))
+        .collect();
+    json!({
+        "pit_id": "pit-2",
+        "took": 1,
+        "hits": { "total": { "value": 25000, "relation": "eq" }, "hits": hits },
+    })
+}
+
+async fn setup(page: Value) -> (MockServer, SearchSessions, Arc<RequestInspector>) {
+    let server = MockServer::start(vec![
+        route("POST", "/logs/_pit", 200, json!({ "id": "pit-1" })),
+        route("POST", "/_search", 200, page),
+    ])
+    .await;
+    let inspector = Arc::new(RequestInspector::default());
+    let client = server.connect_with_inspector(inspector.clone()).await.unwrap();
+    let clients: ClientMap = Arc::new(RwLock::new(HashMap::from([("mock".to_string(), Arc::new(client))])));
+    (server, SearchSessions::new(clients), inspector)
+}
+
+fn search_bodies(inspector: &RequestInspector) -> Vec<Value> {
+    let filter = RequestFilter { path: Some("/_search".to_string()), ..Default::default() };
+    inspector
+        .list("mock", &filter)
+        .into_iter()
+        .rev()
+        .map(|record| serde_json::from_str(record.body.as_deref().unwrap()).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn pages_with_search_after_on_a_point_in_time() {
+    let (server, sessions, inspector) = setup(hits(2)).await;
+    let query = json!({ "size": 2, "query": { "match_all": {} }, "sort": [{ "@timestamp": "desc" }] });
+
+    let first = sessions
+        .open("mock", "logs", query, Duration::from_secs(60), None, None)
+        .await
+        .unwrap();
+    assert_eq!(first.response["hits"]["total"]["value"], 25000);
+    let cursor = first.next_cursor.expect("a full page has a next cursor");
+
+    sessions.page("mock", &cursor, None, None).await.unwrap();
+
+    let bodies = search_bodies(&inspector);
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(bodies[0]["pit"], json!({ "id": "pit-1", "keep_alive": "60s" }));
+    assert_eq!(bodies[0]["sort"], json!([{ "@timestamp": "desc" }, { "_shard_doc": "asc" }]));
+    assert!(bodies[0].get("search_after").is_none());
+    // The second page uses the refreshed id and the sort values of the last hit.
+    assert_eq!(bodies[1]["pit"]["id"], "pit-2");
+    assert_eq!(bodies[1]["search_after"], json!([1, 1]));
+
+    assert!(sessions.close(&first.session_id).await);
+    assert_eq!(server.count("DELETE /_pit"), 1);
+    assert!(!sessions.close(&first.session_id).await);
+}
+
+#[tokio::test]
+async fn last_page_has_no_cursor() {
+    let (_server, sessions, _) = setup(hits(1)).await;
+    let page = sessions
+        .open("mock", "logs", json!({ "size": 2 }), Duration::from_secs(60), None, None)
+        .await
+        .unwrap();
+    assert!(page.next_cursor.is_none());
+}
+
+#[tokio::test]
+async fn rejects_from_and_unknown_cursors() {
+    let (server, sessions, _) = setup(hits(2)).await;
+
+    let err = sessions
+        .open("mock", "logs", json!({ "from": 10000 }), Duration::from_secs(60), None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::ValidationError(_)));
+    assert_eq!(server.count("POST /logs/_pit"), 0);
+
+    let err = sessions.page("mock", "not-a-cursor", None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::ValidationError(_)));
+}
+
+#[tokio::test]
+async fn sessions_expire_and_release_their_point_in_time() {
+    let (server, sessions, _) = setup(hits(2)).await;
+    let page = sessions
+        .open("mock", "logs", json!({ "size": 2 }), Duration::from_millis(50), None, None)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(server.count("DELETE /_pit"), 1);
+
+    let err = sessions.page("mock", &page.next_cursor.unwrap(), None, None).await.unwrap_err();
+    assert!(matches!(err, AppError::StateError(_)));
+}
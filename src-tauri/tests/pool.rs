@@ -0,0 +1,107 @@
+//! Node selection, role filtering and sniffing.
+
+mod common;
+
+use common::{route, MockServer};
+use elastic_eye_lib::{
+    config::{ElasticsearchConfig, NodeSelection, PoolConfig},
+    elasticsearch::{
+        client::{ConnectionInfo, ESClient},
+        pool::{parse_publish_address, NodePool},
+    },
+};
+use elasticsearch::http::transport::ConnectionPool;
+use serde_json::json;
+use url::Url;
+
+fn roles(roles: &[&str]) -> Vec<String> {
+    roles.iter().map(|role| role.to_string()).collect()
+}
+
+fn urls(hosts: &[&str]) -> Vec<Url> {
+    hosts.iter().map(|host| Url::parse(host).unwrap()).collect()
+}
+
+#[test]
+fn publish_addresses_prefer_the_hostname() {
+    let parsed = |address| parse_publish_address(address, "https").unwrap().to_string();
+    assert_eq!(parsed("es-1.internal/10.0.0.1:9200"), "https://es-1.internal:9200/");
+    assert_eq!(parsed("/10.0.0.1:9201"), "https://10.0.0.1:9201/");
+    assert_eq!(parsed("10.0.0.1:9202"), "https://10.0.0.1:9202/");
+    assert_eq!(parsed("es-2.internal/[::1]:9203"), "https://es-2.internal:9203/");
+    assert_eq!(parsed("[::1]:9204"), "https://[::1]:9204/");
+
+    assert!(parse_publish_address("not a host:9200", "https").is_err());
+}
+
+#[test]
+fn role_filter_skips_dedicated_masters_and_unlisted_roles() {
+    let config = PoolConfig::default();
+    assert!(!config.accepts(&roles(&["master"])));
+    assert!(config.accepts(&roles(&["master", "data"])));
+    assert!(config.accepts(&roles(&["master", "ingest"])));
+    assert!(config.accepts(&roles(&["data_hot"])));
+    assert!(PoolConfig { exclude_dedicated_masters: false, ..Default::default() }.accepts(&roles(&["master"])));
+
+    let hot_only = PoolConfig { node_roles: roles(&["data_hot", "data_content"]), ..Default::default() };
+    assert!(hot_only.accepts(&roles(&["data_hot", "ingest"])));
+    assert!(!hot_only.accepts(&roles(&["data_warm"])));
+    assert!(!hot_only.accepts(&roles(&["master"])));
+}
+
+#[test]
+fn selection_rotates_or_sticks_until_a_failure() {
+    let hosts = ["http://es-1:9200/", "http://es-2:9200/"];
+    let picks = |pool: &NodePool| (0..3).map(|_| pool.next().url().to_string()).collect::<Vec<_>>();
+
+    let round_robin = NodePool::new(urls(&hosts), NodeSelection::RoundRobin);
+    assert_eq!(picks(&round_robin), [hosts[0], hosts[1], hosts[0]]);
+
+    let sticky = NodePool::new(urls(&hosts), NodeSelection::Sticky);
+    assert_eq!(picks(&sticky), [hosts[0]; 3]);
+    sticky.mark_failed();
+    assert_eq!(picks(&sticky), [hosts[1]; 3]);
+
+    sticky.reseed(Vec::new());
+    assert_eq!(sticky.urls(), hosts);
+    sticky.reseed(urls(&["http://es-3:9200"]));
+    assert_eq!(sticky.urls(), ["http://es-3:9200/"]);
+}
+
+#[tokio::test]
+async fn sniffing_keeps_only_accepted_nodes() {
+    // The seed host reports the sniffed node at another address.
+    let node = MockServer::start(Vec::new()).await;
+    let port = Url::parse(&node.url).unwrap().port().unwrap();
+    let nodes = json!({
+        "nodes": {
+            "master": { "roles": ["master"], "http": { "publish_address": format!("127.0.0.1:{}", port) } },
+            "hot": { "roles": ["data_hot"], "http": { "publish_address": format!("localhost/127.0.0.1:{}", port) } },
+            "warm": { "roles": ["data_warm"], "http": { "publish_address": "10.0.0.9:9200" } },
+        }
+    });
+    let seed = MockServer::start(vec![route("GET", "/_nodes/http", 200, nodes)]).await;
+    let config = ElasticsearchConfig {
+        pool: PoolConfig {
+            sniff: true,
+            sniff_interval_secs: None,
+            node_roles: roles(&["data_hot"]),
+            ..Default::default()
+        },
+        ..seed.config()
+    };
+    let info = ConnectionInfo {
+        id: "pool".to_string(),
+        name: "pool".to_string(),
+        hosts: config.hosts.clone(),
+        is_active: false,
+    };
+
+    let client = ESClient::new(config, info).await.unwrap();
+    assert_eq!(seed.count("GET /_nodes/http"), 1);
+    assert_eq!(client.active_nodes(), [format!("http://localhost:{}/", port)]);
+
+    client.get_cluster_health().await.unwrap();
+    assert_eq!(node.count("GET /_cluster/health"), 1);
+    assert_eq!(seed.count("GET /_cluster/health"), 0);
+}
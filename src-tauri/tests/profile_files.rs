@@ -0,0 +1,60 @@
+//! Profiles read from the profiles file, overridden and interpolated from the environment.
+//! Kept apart from `profiles.rs` because both set process-wide variables.
+
+use elastic_eye_lib::{
+    db::ConnectionSource,
+    error::AppError,
+    profiles::{get_profile, load_profiles},
+};
+
+const PROFILES: &str = r#"
+[profiles.staging]
+name = "Staging ${unterminated"
+hosts = ["https://staging.internal:9200"]
+username = "${ELASTIC_EYE_TEST_USER}"
+password = "${ELASTIC_EYE_TEST_PASSWORD}"
+timeout_secs = 10
+
+[profiles.missing]
+hosts = ["http://missing.internal:9200"]
+api_key = "${ELASTIC_EYE_TEST_UNSET}"
+"#;
+
+fn key_name(error: &AppError) -> Option<&'static str> {
+    match error {
+        AppError::ValidationError(message) => message.key_name(),
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn file_profiles_are_interpolated_and_overridden_by_the_environment() {
+    let dir = std::env::temp_dir().join(format!("elastic-eye-profile-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("profiles.toml"), PROFILES).unwrap();
+    std::env::set_var("ELASTIC_EYE_CONFIG_DIR", &dir);
+    std::env::set_var("ELASTIC_EYE_TEST_USER", "elastic");
+    std::env::set_var("ELASTIC_EYE_TEST_PASSWORD", "s3cret");
+    std::env::remove_var("ELASTIC_EYE_TEST_UNSET");
+    std::env::set_var("ELASTIC_EYE_PROFILES__STAGING__TIMEOUT_SECS", "20");
+
+    let profiles = load_profiles().unwrap();
+
+    assert_eq!(profiles.connections.len(), 1);
+    let staging = &profiles.connections[0];
+    assert_eq!(staging.id, "profile:staging");
+    assert_eq!(staging.name, "Staging ${unterminated");
+    assert_eq!(staging.source, ConnectionSource::Profile);
+    assert_eq!(staging.config.hosts, ["https://staging.internal:9200"]);
+    assert_eq!(staging.config.username.as_deref(), Some("elastic"));
+    assert_eq!(staging.config.password.as_deref(), Some("s3cret"));
+    assert_eq!(staging.config.timeout_secs, Some(20));
+
+    assert_eq!(profiles.errors.len(), 1);
+    assert_eq!(profiles.errors[0].id, "profile:missing");
+    assert_eq!(key_name(&profiles.errors[0].error), Some("profile.unset_variable"));
+    assert!(profiles.errors[0].error.to_string().contains("ELASTIC_EYE_TEST_UNSET"));
+
+    assert_eq!(key_name(&get_profile("profile:unknown").unwrap_err()), Some("profile.not_found"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
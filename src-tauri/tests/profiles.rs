@@ -0,0 +1,32 @@
+//! Connection profiles read from the environment.
+
+use elastic_eye_lib::profiles::{get_profile, load_profiles};
+
+#[test]
+fn keeps_secrets_as_strings_and_skips_invalid_profiles() {
+    let dir = std::env::temp_dir().join(format!("elastic-eye-profiles-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("ELASTIC_EYE_CONFIG_DIR", &dir);
+    std::env::set_var("ELASTIC_EYE_PROFILES__GOOD__HOSTS", "http://a:9200,http://b:9200");
+    std::env::set_var("ELASTIC_EYE_PROFILES__GOOD__USERNAME", "elastic");
+    std::env::set_var("ELASTIC_EYE_PROFILES__GOOD__PASSWORD", "12345");
+    std::env::set_var("ELASTIC_EYE_PROFILES__GOOD__TIMEOUT_SECS", "30");
+    std::env::set_var("ELASTIC_EYE_PROFILES__GOOD__READ_ONLY", "true");
+    std::env::set_var("ELASTIC_EYE_PROFILES__BAD__HOSTS", "http://c:9200");
+    std::env::set_var("ELASTIC_EYE_PROFILES__BAD__TIMEOUT_SECS", "soon");
+
+    let profiles = load_profiles().unwrap();
+
+    assert_eq!(profiles.connections.len(), 1);
+    let good = &profiles.connections[0];
+    assert_eq!(good.id, "profile:good");
+    assert_eq!(good.config.hosts, vec!["http://a:9200", "http://b:9200"]);
+    assert_eq!(good.config.password.as_deref(), Some("12345"));
+    assert_eq!(good.config.timeout_secs, Some(30));
+    assert!(good.config.read_only);
+
+    assert_eq!(profiles.errors.len(), 1);
+    assert_eq!(profiles.errors[0].id, "profile:bad");
+    assert!(get_profile("profile:bad").is_err());
+    assert!(get_profile("profile:good").is_ok());
+}
@@ -0,0 +1,61 @@
+//! Proxy routing and the no-proxy list.
+
+mod common;
+
+use common::MockServer;
+use elastic_eye_lib::{
+    config::{ElasticsearchConfig, ProxyConfig},
+    elasticsearch::client::{ConnectionInfo, ESClient},
+};
+
+fn proxy(no_proxy: &[&str]) -> ProxyConfig {
+    ProxyConfig {
+        url: "http://proxy.internal:3128".to_string(),
+        username: None,
+        password: None,
+        no_proxy: no_proxy.iter().map(|entry| entry.to_string()).collect(),
+    }
+}
+
+#[test]
+fn no_proxy_entries_match_exact_hosts_and_domain_suffixes() {
+    let listed = proxy(&["localhost", " .internal ", "*.Example.com"]);
+
+    for host in ["localhost", "LOCALHOST", "es.internal", "internal", "a.b.example.com", "example.com"] {
+        assert!(listed.bypasses(host), "{}", host);
+    }
+    for host in ["localhost.localdomain", "es.internal.net", "notexample.com", "example.org"] {
+        assert!(!listed.bypasses(host), "{}", host);
+    }
+
+    assert!(proxy(&["*"]).bypasses("anything.at.all"));
+    assert!(!proxy(&[]).bypasses("localhost"));
+}
+
+#[tokio::test]
+async fn bypassed_nodes_are_reached_directly() {
+    let server = MockServer::start(Vec::new()).await;
+    // Nothing listens here, so any request sent through the proxy fails.
+    let unreachable = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_url = format!("http://{}", unreachable.local_addr().unwrap());
+    drop(unreachable);
+
+    let connect = |no_proxy: &[&str]| {
+        let config = ElasticsearchConfig {
+            proxy: Some(ProxyConfig { url: proxy_url.clone(), ..proxy(no_proxy) }),
+            ..server.config()
+        };
+        let info = ConnectionInfo {
+            id: "proxy".to_string(),
+            name: "proxy".to_string(),
+            hosts: config.hosts.clone(),
+            is_active: false,
+        };
+        ESClient::new(config, info)
+    };
+
+    let client = connect(&["127.0.0.1"]).await.unwrap();
+    client.get_cluster_health().await.unwrap();
+
+    assert!(connect(&["elsewhere.internal"]).await.is_err());
+}
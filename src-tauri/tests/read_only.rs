@@ -0,0 +1,58 @@
+//! Read-only connections refuse writes before anything reaches the cluster.
+
+mod common;
+
+use common::MockServer;
+use elastic_eye_lib::{
+    commands::{ensure_writable, is_write_request},
+    config::ElasticsearchConfig,
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    error::AppError,
+};
+use serde_json::json;
+
+#[test]
+fn write_paths_and_bodies_are_detected() {
+    for index in ["_bulk", "logs/_doc/1", "logs/_update/1", "logs/_update_by_query", "logs/_delete_by_query"] {
+        assert!(is_write_request(index, &json!({})), "{}", index);
+    }
+    for body in [
+        json!({ "doc": { "status": "done" } }),
+        json!({ "script": { "source": "ctx._source.n++" } }),
+        json!({ "source": { "index": "logs" }, "dest": { "index": "archive" } }),
+        json!({ "mappings": { "properties": {} } }),
+    ] {
+        assert!(is_write_request("logs", &body), "{}", body);
+    }
+
+    assert!(!is_write_request("logs", &json!({ "query": { "match_all": {} }, "size": 10 })));
+    assert!(!is_write_request("logs-docs", &json!({ "aggs": { "by_status": { "terms": { "field": "status" } } } })));
+    // Only top-level keys count; `_source` filtering and nested `doc` fields are reads.
+    assert!(!is_write_request("logs", &json!({ "_source": ["doc"], "query": { "term": { "doc": "x" } } })));
+}
+
+#[tokio::test]
+async fn only_read_only_connections_refuse_writes() {
+    let server = MockServer::start(Vec::new()).await;
+    let connect = |read_only| {
+        let config = ElasticsearchConfig { read_only, ..server.config() };
+        let info = ConnectionInfo {
+            id: "prod".to_string(),
+            name: "Production".to_string(),
+            hosts: config.hosts.clone(),
+            is_active: false,
+        };
+        ESClient::new(config, info)
+    };
+
+    ensure_writable(&connect(false).await.unwrap(), "action.delete_snapshot").unwrap();
+
+    let err = ensure_writable(&connect(true).await.unwrap(), "action.delete_snapshot").unwrap_err();
+    match err {
+        AppError::ReadOnlyError(message) => {
+            assert_eq!(message.key_name(), Some("error.read_only_action"));
+            assert!(message.to_string().contains("Production"));
+        }
+        other => panic!("expected a read-only error, got {:?}", other),
+    }
+}
@@ -0,0 +1,154 @@
+//! Requests recorded by the request inspector against a mock Elasticsearch.
+
+mod common;
+
+use std::sync::Arc;
+use common::{es_error, route, MockServer};
+use elastic_eye_lib::{
+    config::CustomHeader,
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    inspector::{self, RequestFilter, RequestInspector},
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn records_method_path_query_and_status() {
+    let server = MockServer::start(vec![route("GET", "/_cat/indices", 200, json!([]))]).await;
+    let inspector = Arc::new(RequestInspector::default());
+    let client = server.connect_with_inspector(inspector.clone()).await.unwrap();
+
+    client.list_indices(None).await.unwrap();
+    let records = inspector.list("mock", &RequestFilter::default());
+    let latest = &records[0];
+    assert_eq!(latest.method, "GET");
+    assert_eq!(latest.node, server.url);
+    assert_eq!(latest.path, "/_cat/indices");
+    assert!(latest.query.as_deref().unwrap().contains("format=json"));
+    assert_eq!(latest.status, Some(200));
+    assert_eq!(latest.response_size, Some(2));
+
+    // Connecting pinged the cluster and read its version first.
+    let paths: Vec<_> = records.iter().rev().map(|r| format!("{} {}", r.method, r.path)).collect();
+    assert_eq!(paths[..2], ["HEAD /", "GET /"]);
+}
+
+#[tokio::test]
+async fn records_bodies_and_failures() {
+    let server = MockServer::start(vec![route(
+        "POST",
+        "/logs/_search",
+        400,
+        es_error("parsing_exception", "unknown query [matchh]"),
+    )])
+    .await;
+    let inspector = Arc::new(RequestInspector::default());
+    let client = server.connect_with_inspector(inspector.clone()).await.unwrap();
+
+    client.search("logs", json!({ "query": { "matchh": {} } }), None, None).await.unwrap_err();
+
+    let failed = inspector.list("mock", &RequestFilter { failed_only: true, ..Default::default() });
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].status, Some(400));
+    assert_eq!(failed[0].body.as_deref(), Some(r#"{"query":{"matchh":{}}}"#));
+
+    let curl = inspector::to_curl(&failed[0], Some(client.get_config()));
+    assert_eq!(
+        curl,
+        format!(
+            "curl -X POST '{}/logs/_search' -H 'Content-Type: application/json' -d '{{\"query\":{{\"matchh\":{{}}}}}}'",
+            server.url
+        )
+    );
+}
+
+#[tokio::test]
+async fn filters_and_clears_by_connection() {
+    let server = MockServer::start(vec![]).await;
+    let inspector = Arc::new(RequestInspector::default());
+    let client = server.connect_with_inspector(inspector.clone()).await.unwrap();
+    client.get_cluster_health().await.unwrap();
+
+    let filter = RequestFilter { path: Some("_cluster".to_string()), ..Default::default() };
+    let health = inspector.list("mock", &filter);
+    assert_eq!(health.len(), 1);
+    assert_eq!(health[0].path, "/_cluster/health");
+    assert!(inspector.get("mock", health[0].id).is_some());
+
+    let heads = inspector.list("mock", &RequestFilter { method: Some("head".to_string()), ..Default::default() });
+    assert!(heads.iter().all(|r| r.method == "HEAD"));
+    assert!(inspector.list("other", &RequestFilter::default()).is_empty());
+
+    inspector.clear("mock").await.unwrap();
+    assert!(inspector.list("mock", &RequestFilter::default()).is_empty());
+}
+
+#[tokio::test]
+async fn transport_failures_are_recorded_with_the_error() {
+    let server = MockServer::start(vec![]).await;
+    let mut config = server.config();
+    config.hosts = vec!["http://127.0.0.1:1".to_string()];
+    let info = ConnectionInfo {
+        id: "down".to_string(),
+        name: "down".to_string(),
+        hosts: config.hosts.clone(),
+        is_active: false,
+    };
+    let inspector = Arc::new(RequestInspector::default());
+    assert!(ESClient::with_inspector(config, info, inspector.clone()).await.is_err());
+
+    // The initial ping and both retries.
+    let records = inspector.list("down", &RequestFilter::default());
+    assert_eq!(records.len(), 3);
+    for record in records {
+        assert_eq!((record.method.as_str(), record.path.as_str()), ("HEAD", "/"));
+        assert_eq!(record.node, "http://127.0.0.1:1");
+        assert!(record.status.is_none());
+        assert!(record.error.is_some());
+    }
+}
+
+#[tokio::test]
+async fn masks_repository_settings_and_secrets() {
+    let server = MockServer::start(vec![]).await;
+    let inspector = Arc::new(RequestInspector::default());
+    let client = server.connect_with_inspector(inspector.clone()).await.unwrap();
+
+    client
+        .create_snapshot_repository("s3", "s3", json!({ "bucket": "backups", "secret_key": "hunter2" }))
+        .await
+        .unwrap();
+    client
+        .search("logs", json!({ "query": { "term": { "session_token": "abc" } }, "tokenizer": "standard" }), None, None)
+        .await
+        .unwrap();
+
+    let records = inspector.list("mock", &RequestFilter::default());
+    let search: serde_json::Value = serde_json::from_str(records[0].body.as_deref().unwrap()).unwrap();
+    assert_eq!(search["query"]["term"]["session_token"], inspector::REDACTED);
+    assert_eq!(search["tokenizer"], "standard");
+    let repository: serde_json::Value = serde_json::from_str(records[1].body.as_deref().unwrap()).unwrap();
+    assert_eq!(repository, json!({ "type": "s3", "settings": inspector::REDACTED }));
+}
+
+#[tokio::test]
+async fn curl_includes_custom_headers() {
+    let server = MockServer::start(vec![]).await;
+    let mut config = server.config();
+    config.headers.insert("X-Tenant".to_string(), CustomHeader { value: Some("acme".to_string()), secret: false });
+    config.headers.insert("X-Tenant-Token".to_string(), CustomHeader { value: Some("s3cr3t".to_string()), secret: true });
+    let inspector = Arc::new(RequestInspector::default());
+    let info = ConnectionInfo { id: "mock".to_string(), name: "mock".to_string(), hosts: config.hosts.clone(), is_active: false };
+    let client = ESClient::with_inspector(config, info, inspector.clone()).await.unwrap();
+
+    client.get_cluster_health().await.unwrap();
+    let record = &inspector.list("mock", &RequestFilter::default())[0];
+    let curl = inspector::to_curl(record, Some(client.get_config()));
+    assert_eq!(
+        curl,
+        format!(
+            "curl -H 'X-Tenant: acme' -H \"X-Tenant-Token: $ES_HEADER_X_TENANT_TOKEN\" '{}/_cluster/health'",
+            server.url
+        )
+    );
+    assert!(!curl.contains("s3cr3t"));
+}
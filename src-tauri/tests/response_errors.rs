@@ -0,0 +1,252 @@
+//! Failure paths of `ESClient` against a minimal mock Elasticsearch.
+
+mod common;
+
+use common::{es_error, route, MockServer, Route};
+use std::time::Duration;
+use elastic_eye_lib::{config::RetryPolicy, error::AppError};
+use serde_json::{json, Value};
+
+fn response_error(err: AppError) -> Box<elastic_eye_lib::error::ResponseError> {
+    match err {
+        AppError::ResponseError(e) => e,
+        other => panic!("expected a response error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn rejected_credentials_fail_the_connection() {
+    let server = MockServer::start(vec![route(
+        "HEAD",
+        "/",
+        401,
+        Value::Null,
+    )])
+    .await;
+
+    let err = server.connect().await.err().expect("connection should fail");
+    assert_eq!(err.code(), "unauthorized");
+    assert_eq!(err.status(), Some(401));
+}
+
+#[tokio::test]
+async fn create_snapshot_reports_missing_repository() {
+    let server = MockServer::start(vec![route(
+        "POST",
+        "/_snapshot/backups/nightly",
+        404,
+        es_error("repository_missing_exception", "[backups] missing"),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = client.create_snapshot("backups", "nightly", None).await.unwrap_err();
+    assert_eq!(err.code(), "not_found");
+    let err = response_error(err);
+    assert_eq!(err.status, 404);
+    assert_eq!(err.error.as_ref().unwrap().kind, "repository_missing_exception");
+    assert_eq!(err.root_cause[0].reason.as_deref(), Some("[backups] missing"));
+    assert_eq!(err.request.method, "POST");
+    assert!(err.request.path.starts_with("/_snapshot/backups/nightly"));
+}
+
+#[tokio::test]
+async fn delete_snapshot_reports_forbidden() {
+    let server = MockServer::start(vec![route(
+        "DELETE",
+        "/_snapshot/backups/nightly",
+        403,
+        es_error("security_exception", "action [cluster:admin/snapshot/delete] is unauthorized"),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = client.delete_snapshot("backups", "nightly").await.unwrap_err();
+    assert_eq!(err.code(), "forbidden");
+    assert!(err.to_string().contains("is unauthorized"));
+}
+
+#[tokio::test]
+async fn restore_snapshot_keeps_non_json_bodies() {
+    let server = MockServer::start(vec![Route {
+        method: "POST",
+        path: "/_snapshot/backups/nightly/_restore",
+        status: 500,
+        body: "upstream exploded".to_string(),
+    }])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = response_error(client.restore_snapshot("backups", "nightly", None).await.unwrap_err());
+    assert_eq!(err.status, 500);
+    assert!(err.error.is_none());
+    assert_eq!(err.body.as_deref(), Some("upstream exploded"));
+}
+
+#[tokio::test]
+async fn create_repository_reports_the_innermost_cause() {
+    let server = MockServer::start(vec![route(
+        "POST",
+        "/_snapshot/backups",
+        500,
+        json!({
+            "error": {
+                "root_cause": [{ "type": "repository_verification_exception", "reason": "[backups] path is not accessible" }],
+                "type": "repository_verification_exception",
+                "reason": "[backups] path is not accessible",
+                "caused_by": { "type": "access_denied_exception", "reason": "/mnt/backups/tests-x" },
+            },
+            "status": 500,
+        }),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = client
+        .create_snapshot_repository("backups", "fs", json!({ "location": "/mnt/backups" }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "server_error");
+    let err = response_error(err);
+    assert_eq!(err.reason(), Some("/mnt/backups/tests-x"));
+    assert_eq!(err.error.unwrap().caused_by.unwrap().kind, "access_denied_exception");
+}
+
+#[tokio::test]
+async fn search_reports_query_errors() {
+    let server = MockServer::start(vec![route(
+        "POST",
+        "/logs/_search",
+        400,
+        es_error("parsing_exception", "unknown query [matchh]"),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = client
+        .search("logs", json!({ "query": { "matchh": {} } }), None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "bad_request");
+
+    let serialized = serde_json::to_value(&err).unwrap();
+    assert_eq!(serialized["code"], "bad_request");
+    assert_eq!(serialized["status"], 400);
+    assert_eq!(serialized["details"]["error"]["type"], "parsing_exception");
+    assert_eq!(serialized["details"]["request"]["method"], "POST");
+}
+
+#[tokio::test]
+async fn retried_statuses_fail_once_retries_run_out() {
+    let server = MockServer::start(vec![route(
+        "GET",
+        "/_cluster/health",
+        503,
+        es_error("cluster_block_exception", "blocked by: [SERVICE_UNAVAILABLE/1/state not recovered]"),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = client.get_cluster_health().await.unwrap_err();
+    assert_eq!(err.status(), Some(503));
+    assert_eq!(server.count("GET /_cluster/health"), 3);
+}
+
+#[tokio::test]
+async fn retried_statuses_recover_and_others_fail_at_once() {
+    let server = MockServer::start(vec![
+        route("GET", "/_snapshot", 503, json!({})),
+        route("GET", "/_snapshot", 200, json!({})),
+        route("GET", "/_snapshot/backups/_all", 500, es_error("exception", "boom")),
+    ])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    assert!(client.list_snapshot_repositories().await.unwrap().is_empty());
+    assert_eq!(server.count("GET /_snapshot"), 2);
+
+    let err = client.list_snapshots("backups").await.unwrap_err();
+    assert_eq!(err.status(), Some(500));
+    assert_eq!(server.count("GET /_snapshot/backups/_all"), 1);
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let policy = RetryPolicy { initial_backoff_ms: 200, max_backoff_ms: 1_000, ..Default::default() };
+    let delays: Vec<_> = (0..5).map(|attempt| policy.backoff(attempt)).collect();
+    assert_eq!(delays, [200, 400, 800, 1_000, 1_000].map(Duration::from_millis));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(1_000));
+}
+
+#[tokio::test]
+async fn old_string_errors_are_decoded() {
+    let server = MockServer::start(vec![route(
+        "GET",
+        "/_snapshot",
+        400,
+        json!({ "error": "Incorrect HTTP method", "status": 400 }),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = response_error(client.list_snapshot_repositories().await.unwrap_err());
+    assert_eq!(err.reason(), Some("Incorrect HTTP method"));
+}
+
+#[tokio::test]
+async fn successful_responses_still_succeed() {
+    let server = MockServer::start(vec![route(
+        "GET",
+        "/_snapshot",
+        200,
+        json!({ "backups": { "type": "fs", "settings": { "location": "/mnt/backups" } } }),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let repositories = client.list_snapshot_repositories().await.unwrap();
+    assert_eq!(repositories.len(), 1);
+    assert_eq!(repositories[0].name, "backups");
+}
+
+#[tokio::test]
+async fn transport_failures_are_only_retried_for_idempotent_requests() {
+    let server = MockServer::start(vec![
+        route("POST", "/_snapshot/backups/nightly", 0, json!({})),
+        route("GET", "/_snapshot/backups/_all", 0, json!({})),
+        route("POST", "/logs/_search", 0, json!({})),
+        route("DELETE", "/_snapshot/backups/nightly", 0, json!({})),
+    ])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    assert!(client.create_snapshot("backups", "nightly", None).await.is_err());
+    assert_eq!(server.count("POST /_snapshot/backups/nightly"), 1);
+
+    assert!(client.delete_snapshot("backups", "nightly").await.is_err());
+    assert_eq!(server.count("DELETE /_snapshot/backups/nightly"), 1);
+
+    assert!(client.list_snapshots("backups").await.is_err());
+    assert_eq!(server.count("GET /_snapshot/backups/_all"), 3);
+
+    assert!(client.search("logs", json!({}), None, None).await.is_err());
+    assert_eq!(server.count("POST /logs/_search"), 3);
+}
+
+#[tokio::test]
+async fn repository_without_a_type_is_malformed() {
+    let server = MockServer::start(vec![route(
+        "GET",
+        "/_snapshot",
+        200,
+        json!({ "backups": { "settings": { "location": "/mnt/backups" } } }),
+    )])
+    .await;
+    let client = server.connect().await.unwrap();
+
+    let err = client.list_snapshot_repositories().await.unwrap_err();
+    assert!(matches!(
+        err,
+        AppError::ElasticsearchError(message) if message.key_name() == Some("response.missing_field")
+    ));
+}
@@ -0,0 +1,78 @@
+//! Saved connections keep their whole configuration across restarts.
+
+use std::collections::BTreeMap;
+use elastic_eye_lib::{
+    config::{
+        CustomHeader, ElasticsearchConfig, Environment, PoolConfig, ProxyConfig, RetryPolicy,
+        VerificationMode,
+    },
+    db::{ConnectionSource, Database, SavedConnection},
+};
+
+fn full_config() -> ElasticsearchConfig {
+    ElasticsearchConfig {
+        hosts: vec!["https://es-1.internal:9243".to_string(), "https://es-2.internal:9243".to_string()],
+        api_key: Some("id:key".to_string()),
+        ca_cert_path: Some("/etc/elastic/ca.pem".to_string()),
+        verification_mode: VerificationMode::Certificate,
+        timeout_secs: Some(45),
+        retry: RetryPolicy { max_retries: 5, retry_on_status: vec![429], ..Default::default() },
+        pool: PoolConfig { node_roles: vec!["data_hot".to_string()], ..Default::default() },
+        environment: Some(Environment::Custom("qa".to_string())),
+        read_only: true,
+        proxy: Some(ProxyConfig {
+            url: "http://proxy.internal:3128".to_string(),
+            username: Some("proxy".to_string()),
+            password: Some("proxy-secret".to_string()),
+            no_proxy: vec![".internal".to_string()],
+        }),
+        headers: BTreeMap::from([
+            ("X-Tenant".to_string(), CustomHeader { value: Some("blue".to_string()), secret: false }),
+            ("Cookie".to_string(), CustomHeader { value: Some("session=1".to_string()), secret: false }),
+        ]),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn saved_config_round_trips_through_the_store() {
+    let path = std::env::temp_dir().join(format!("elastic-eye-saved-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let db = Database::new(path.clone()).await.unwrap();
+    db.unlock("master".to_string()).await.unwrap();
+    db.save_connection(SavedConnection {
+        id: "multi".to_string(),
+        name: "Multi node".to_string(),
+        config: full_config(),
+        created_at: 1,
+        last_used_at: 1,
+        source: ConnectionSource::Database,
+    })
+    .await
+    .unwrap();
+    drop(db);
+
+    // Secrets never reach the file in plaintext.
+    let raw: String = rusqlite::Connection::open(&path)
+        .unwrap()
+        .query_row("SELECT config FROM connections WHERE id = 'multi'", [], |row| row.get(0))
+        .unwrap();
+    for secret in ["id:key", "proxy-secret", "session=1"] {
+        assert!(!raw.contains(secret), "{} stored in plaintext", secret);
+    }
+    assert!(raw.contains("blue"));
+
+    let db = Database::new(path.clone()).await.unwrap();
+    db.unlock("master".to_string()).await.unwrap();
+    let saved = db.get_connection("multi").await.unwrap();
+    assert_eq!(saved.name, "Multi node");
+    assert_eq!(
+        serde_json::to_value(&saved.config).unwrap(),
+        serde_json::to_value(full_config()).unwrap()
+    );
+
+    db.update_last_used("multi".to_string()).await.unwrap();
+    assert!(db.get_connection("multi").await.unwrap().last_used_at > 1);
+    let _ = std::fs::remove_file(&path);
+}
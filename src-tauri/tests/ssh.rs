@@ -0,0 +1,150 @@
+//! SSH tunnels: host key checking against known_hosts and the settings they allow.
+
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+use aes_gcm::aead::OsRng;
+use common::MockServer;
+use elastic_eye_lib::{
+    config::{ElasticsearchConfig, SshTunnelConfig, VerificationMode},
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    error::AppError,
+};
+use russh::{
+    keys::{Algorithm, PrivateKey},
+    server::{self, Auth, Msg, Session},
+    Channel,
+};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Accepts the password `secret` and forwards `direct-tcpip` channels.
+struct Bastion;
+
+impl server::Handler for Bastion {
+    type Error = russh::Error;
+
+    async fn auth_password(&mut self, _user: &str, password: &str) -> Result<Auth, Self::Error> {
+        Ok(if password == "secret" { Auth::Accept } else { Auth::reject() })
+    }
+
+    async fn channel_open_direct_tcpip(
+        &mut self,
+        channel: Channel<Msg>,
+        host_to_connect: &str,
+        port_to_connect: u32,
+        _originator_address: &str,
+        _originator_port: u32,
+        _session: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        let Ok(mut upstream) = TcpStream::connect((host_to_connect, port_to_connect as u16)).await else {
+            return Ok(false);
+        };
+        tokio::spawn(async move {
+            let mut channel = channel.into_stream();
+            let _ = tokio::io::copy_bidirectional(&mut channel, &mut upstream).await;
+        });
+        Ok(true)
+    }
+}
+
+async fn bastion(key: PrivateKey) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let config = Arc::new(server::Config { keys: vec![key], ..Default::default() });
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            if let Ok(session) = server::run_stream(config.clone(), stream, Bastion).await {
+                tokio::spawn(session);
+            }
+        }
+    });
+    port
+}
+
+fn tunnel(port: u16, accept_unknown_host_key: bool) -> SshTunnelConfig {
+    SshTunnelConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        user: "elastic".to_string(),
+        key_path: None,
+        key_passphrase: None,
+        password: Some("secret".to_string()),
+        accept_unknown_host_key,
+    }
+}
+
+async fn connect(server: &MockServer, tunnel: SshTunnelConfig) -> Result<ESClient, AppError> {
+    let config = ElasticsearchConfig { ssh_tunnel: Some(tunnel), ..server.config() };
+    let info = ConnectionInfo {
+        id: "ssh".to_string(),
+        name: "ssh".to_string(),
+        hosts: config.hosts.clone(),
+        is_active: false,
+    };
+    ESClient::new(config, info).await
+}
+
+fn is_refused(result: &Result<ESClient, AppError>) -> bool {
+    matches!(result, Err(AppError::ConnectionError(message)) if message.key_name() == Some("ssh.unreachable"))
+}
+
+fn known_hosts(home: &Path, port: u16, key: Option<&PrivateKey>) {
+    let line = key
+        .map(|key| format!("[127.0.0.1]:{} {}\n", port, key.public_key().to_openssh().unwrap()))
+        .unwrap_or_default();
+    std::fs::write(home.join(".ssh").join("known_hosts"), line).unwrap();
+}
+
+#[tokio::test]
+async fn bastion_host_keys_are_checked_against_known_hosts() {
+    // known_hosts is looked up under $HOME, so point it at a scratch directory.
+    let home = std::env::temp_dir().join(format!("elastic-eye-ssh-{}", std::process::id()));
+    std::fs::create_dir_all(home.join(".ssh")).unwrap();
+    std::env::set_var("HOME", &home);
+
+    let key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let other = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+    let port = bastion(key.clone()).await;
+    let server = MockServer::start(Vec::new()).await;
+
+    // Unknown host: only accepted when allowed.
+    known_hosts(&home, port, None);
+    assert!(is_refused(&connect(&server, tunnel(port, false)).await));
+    let client = connect(&server, tunnel(port, true)).await.unwrap();
+    client.get_cluster_health().await.unwrap();
+    assert_eq!(server.count("GET /_cluster/health"), 1);
+
+    // Known host with the recorded key.
+    known_hosts(&home, port, Some(&key));
+    connect(&server, tunnel(port, false)).await.unwrap();
+
+    // A changed key is refused even when unknown hosts are accepted.
+    known_hosts(&home, port, Some(&other));
+    assert!(is_refused(&connect(&server, tunnel(port, true)).await));
+
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn full_verification_behind_a_tunnel_needs_a_pin() {
+    let config = |hosts: &str, verification_mode, ca_fingerprint: Option<String>| ElasticsearchConfig {
+        hosts: vec![hosts.to_string()],
+        verification_mode,
+        ca_fingerprint,
+        ca_cert_path: Some("/etc/elastic/ca.pem".to_string()),
+        ssh_tunnel: Some(tunnel(22, false)),
+        ..Default::default()
+    };
+
+    let result = config("https://es.internal:9200", VerificationMode::Full, None).validate();
+    assert!(matches!(
+        result,
+        Err(AppError::ValidationError(message)) if message.key_name() == Some("config.ssh_full_verification")
+    ));
+
+    let pin = Some("AB:".repeat(31) + "AB");
+    config("https://es.internal:9200", VerificationMode::Full, pin).validate().unwrap();
+    config("https://es.internal:9200", VerificationMode::Certificate, None).validate().unwrap();
+    config("http://es.internal:9200", VerificationMode::Full, None).validate().unwrap();
+}
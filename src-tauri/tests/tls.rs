@@ -0,0 +1,246 @@
+//! Certificate fingerprint pinning against a TLS mock with a private CA.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use elastic_eye_lib::{
+    config::{ElasticsearchConfig, PoolConfig, VerificationMode},
+    elasticsearch::client::{ConnectionInfo, ESClient},
+    error::AppError,
+};
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair, KeyUsagePurpose};
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+    ServerConfig,
+};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+const ROOT: &str = r#"{"name":"tls","version":{"number":"8.11.0","build_flavor":"default"},"tagline":"You Know, for Search"}"#;
+
+struct Chain {
+    ca: CertificateDer<'static>,
+    ca_pem: String,
+    leaf: CertificateDer<'static>,
+    key: Vec<u8>,
+}
+
+/// A private CA and a leaf it issued for `san`.
+fn chain(ca_name: &str, san: &str) -> Chain {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.distinguished_name.push(DnType::CommonName, ca_name);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign];
+    let ca = params.self_signed(&ca_key).unwrap();
+
+    let key = KeyPair::generate().unwrap();
+    let leaf = CertificateParams::new(vec![san.to_string()])
+        .unwrap()
+        .signed_by(&key, &ca, &ca_key)
+        .unwrap();
+    Chain {
+        ca: ca.der().clone(),
+        ca_pem: ca.pem(),
+        leaf: leaf.der().clone(),
+        key: key.serialize_der(),
+    }
+}
+
+fn fingerprint(cert: &[u8]) -> String {
+    Sha256::digest(cert)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn acceptor(chain: &Chain) -> TlsAcceptor {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(chain.key.clone()));
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![chain.leaf.clone(), chain.ca.clone()], key)
+        .unwrap();
+    TlsAcceptor::from(Arc::new(config))
+}
+
+/// Serves the root endpoint over TLS, one request per connection so every request
+/// needs a new handshake. Connections present `first` until `swap` is set, then `then`.
+async fn serve(first: &Chain, then: &Chain, swap: Arc<AtomicBool>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("https://{}", listener.local_addr().unwrap());
+    let acceptors = Arc::new([acceptor(first), acceptor(then)]);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptors[swap.load(Ordering::SeqCst) as usize].clone();
+            tokio::spawn(async move {
+                let Ok(stream) = acceptor.accept(stream).await else { return };
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                if reader.read_exact(&mut body).await.is_err() {
+                    return;
+                }
+
+                let body = if request_line.starts_with("GET / ") { ROOT } else { "{}" };
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nx-elastic-product: Elasticsearch\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                let stream = reader.get_mut();
+                let _ = stream.write_all(head.as_bytes()).await;
+                if !request_line.starts_with("HEAD ") {
+                    let _ = stream.write_all(body.as_bytes()).await;
+                }
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    url
+}
+
+async fn connect(url: &str, mode: VerificationMode, fingerprint: Option<String>) -> Result<ESClient, AppError> {
+    let config = ElasticsearchConfig {
+        hosts: vec![url.to_string()],
+        verification_mode: mode,
+        ca_fingerprint: fingerprint,
+        timeout_secs: Some(5),
+        ..Default::default()
+    };
+    let info = ConnectionInfo {
+        id: "tls".to_string(),
+        name: "tls".to_string(),
+        hosts: config.hosts.clone(),
+        is_active: false,
+    };
+    ESClient::new(config, info).await
+}
+
+fn is_mismatch(result: &Result<ESClient, AppError>) -> bool {
+    matches!(result, Err(AppError::ConnectionError(message)) if message.key_name() == Some("tls.fingerprint_mismatch"))
+}
+
+#[tokio::test]
+async fn pinned_ca_is_trusted_in_every_mode() {
+    let chain = chain("Pinned CA", "127.0.0.1");
+    let url = serve(&chain, &chain, Arc::default()).await;
+
+    // Without the pin the private CA is unknown.
+    assert!(connect(&url, VerificationMode::Full, None).await.is_err());
+
+    for mode in [VerificationMode::Full, VerificationMode::Certificate, VerificationMode::None] {
+        for pin in [fingerprint(&chain.ca), fingerprint(&chain.leaf)] {
+            let client = connect(&url, mode, Some(pin)).await.unwrap();
+            client.get_cluster_health().await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn full_mode_checks_the_hostname_against_the_pinned_chain() {
+    let chain = chain("Pinned CA", "elasticsearch.internal");
+    let url = serve(&chain, &chain, Arc::default()).await;
+    let pin = fingerprint(&chain.ca);
+
+    let result = connect(&url, VerificationMode::Full, Some(pin.clone())).await;
+    assert!(matches!(result, Err(AppError::ConnectionError(message)) if message.key_name() == Some("tls.handshake_failed")));
+
+    connect(&url, VerificationMode::Certificate, Some(pin)).await.unwrap();
+}
+
+#[tokio::test]
+async fn mismatched_pin_is_rejected() {
+    let pinned = chain("Pinned CA", "127.0.0.1");
+    let other = chain("Other CA", "127.0.0.1");
+    let url = serve(&pinned, &pinned, Arc::default()).await;
+
+    for mode in [VerificationMode::Full, VerificationMode::None] {
+        for pin in [fingerprint(&other.ca), fingerprint(&other.leaf)] {
+            assert!(is_mismatch(&connect(&url, mode, Some(pin)).await));
+        }
+    }
+}
+
+#[tokio::test]
+async fn system_trusted_certificate_is_rejected_after_connect() {
+    let pinned = chain("Pinned CA", "127.0.0.1");
+    let other = chain("Other CA", "127.0.0.1");
+    // Make the other CA a system root, so only the pin can tell the chains apart.
+    let roots = std::env::temp_dir().join(format!("elastic-eye-roots-{}.pem", std::process::id()));
+    std::fs::write(&roots, &other.ca_pem).unwrap();
+    std::env::set_var("SSL_CERT_FILE", &roots);
+
+    let swap = Arc::new(AtomicBool::new(false));
+    let url = serve(&pinned, &other, swap.clone()).await;
+
+    let client = connect(&url, VerificationMode::Full, Some(fingerprint(&pinned.ca))).await.unwrap();
+    client.get_cluster_health().await.unwrap();
+
+    swap.store(true, Ordering::SeqCst);
+    assert!(client.get_cluster_health().await.is_err());
+    assert!(is_mismatch(&connect(&url, VerificationMode::Full, Some(fingerprint(&pinned.ca))).await));
+
+    // The same chain is fine where nothing is pinned.
+    connect(&url, VerificationMode::Full, None).await.unwrap();
+    let _ = std::fs::remove_file(&roots);
+}
+
+#[test]
+fn pin_combinations_are_validated() {
+    let pin = "AB:".repeat(31) + "AB";
+    let pinned = || ElasticsearchConfig {
+        hosts: vec!["https://localhost:9200".to_string()],
+        ca_fingerprint: Some(pin.clone()),
+        ..Default::default()
+    };
+    let rejected = |config: ElasticsearchConfig| match config.validate() {
+        Err(AppError::ValidationError(message)) => message.key_name(),
+        other => panic!("expected a validation error, got {:?}", other),
+    };
+
+    // The pin stands in for the CA file certificate mode otherwise needs.
+    ElasticsearchConfig { verification_mode: VerificationMode::Certificate, ..pinned() }.validate().unwrap();
+
+    let short = ElasticsearchConfig { ca_fingerprint: Some("abcd".to_string()), ..pinned() };
+    assert_eq!(rejected(short), Some("config.invalid_fingerprint"));
+
+    let http = ElasticsearchConfig { hosts: vec!["http://localhost:9200".to_string()], ..pinned() };
+    assert_eq!(rejected(http), Some("config.fingerprint_https"));
+
+    let cloud = ElasticsearchConfig {
+        hosts: Vec::new(),
+        cloud_id: Some(format!("deployment:{}", BASE64.encode("eu-west-1.aws.found.io$es$kb"))),
+        api_key: Some("id:key".to_string()),
+        ..pinned()
+    };
+    assert_eq!(rejected(cloud), Some("config.fingerprint_cloud"));
+
+    let sniffing = ElasticsearchConfig { pool: PoolConfig { sniff: true, ..Default::default() }, ..pinned() };
+    assert_eq!(rejected(sniffing), Some("config.sniff_pinned"));
+}
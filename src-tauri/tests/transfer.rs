@@ -0,0 +1,112 @@
+//! Importing connection files into a fresh database.
+
+use std::path::PathBuf;
+use elastic_eye_lib::{
+    config::ElasticsearchConfig,
+    db::{ConnectionSource, Database, SavedConnection},
+    error::{AppError, AppResult},
+    transfer::{import_connections, ConflictStrategy, ImportSummary},
+};
+use serde_json::json;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("elastic-eye-transfer-{}-{}", std::process::id(), name))
+}
+
+fn connection(id: &str, name: &str, host: &str) -> SavedConnection {
+    SavedConnection {
+        id: id.to_string(),
+        name: name.to_string(),
+        config: ElasticsearchConfig {
+            hosts: vec![host.to_string()],
+            ..Default::default()
+        },
+        created_at: 1,
+        last_used_at: 1,
+        source: ConnectionSource::Database,
+    }
+}
+
+async fn database(name: &str) -> Database {
+    let db_path = temp_path(&format!("{}.db", name));
+    let _ = std::fs::remove_file(&db_path);
+    Database::new(db_path).await.unwrap()
+}
+
+async fn import_into(db: &Database, name: &str, connections: Vec<SavedConnection>, on_conflict: ConflictStrategy) -> AppResult<ImportSummary> {
+    let file = temp_path(&format!("{}.json", name));
+    std::fs::write(&file, json!({ "version": 1, "connections": connections }).to_string()).unwrap();
+    let result = import_connections(db, &file, on_conflict, None).await;
+    std::fs::remove_file(&file).unwrap();
+    result
+}
+
+async fn import(name: &str, connections: Vec<SavedConnection>, on_conflict: ConflictStrategy) -> (Database, usize, usize, usize, usize) {
+    let db = database(name).await;
+    let summary = import_into(&db, name, connections, on_conflict).await.unwrap();
+    (db, summary.imported, summary.overwritten, summary.renamed, summary.skipped)
+}
+
+#[tokio::test]
+async fn duplicates_within_a_file_conflict_with_each_other() {
+    let duplicates = || vec![
+        connection("prod", "Production", "http://a:9200"),
+        connection("prod", "Production copy", "http://b:9200"),
+        connection("other", "Production", "http://c:9200"),
+    ];
+
+    let (db, imported, _, _, skipped) = import("skip", duplicates(), ConflictStrategy::Skip).await;
+    assert_eq!((imported, skipped), (1, 2));
+    let saved = db.list_connections().await.unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].config.hosts, vec!["http://a:9200"]);
+
+    let (db, imported, _, renamed, _) = import("rename", duplicates(), ConflictStrategy::Rename).await;
+    assert_eq!((imported, renamed), (1, 2));
+    let mut names: Vec<String> = db.list_connections().await.unwrap().into_iter().map(|c| c.name).collect();
+    names.sort();
+    assert_eq!(names, vec!["Production", "Production (2)", "Production copy (2)"]);
+
+    let (db, imported, overwritten, _, _) = import("overwrite", duplicates(), ConflictStrategy::Overwrite).await;
+    assert_eq!((imported, overwritten), (1, 2));
+    let saved = db.list_connections().await.unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].config.hosts, vec!["http://c:9200"]);
+}
+
+#[tokio::test]
+async fn an_invalid_entry_rejects_the_whole_file() {
+    let db = database("invalid").await;
+    let connections = vec![
+        connection("prod", "Production", "http://a:9200"),
+        connection("broken", "Broken", "not a url"),
+    ];
+
+    let result = import_into(&db, "invalid", connections, ConflictStrategy::Skip).await;
+    assert!(matches!(
+        result,
+        Err(AppError::ValidationError(message)) if message.key_name() == Some("transfer.invalid_connection")
+    ));
+    assert!(db.list_connections().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn overwriting_two_different_rows_is_rejected() {
+    let db = database("ambiguous").await;
+    db.save_connection(connection("a", "A", "http://a:9200")).await.unwrap();
+    db.save_connection(connection("b", "B", "http://b:9200")).await.unwrap();
+
+    let result = import_into(&db, "ambiguous", vec![connection("a", "B", "http://c:9200")], ConflictStrategy::Overwrite).await;
+    assert!(matches!(
+        result,
+        Err(AppError::ValidationError(message)) if message.key_name() == Some("transfer.ambiguous_overwrite")
+    ));
+    let mut hosts: Vec<Vec<String>> = db.list_connections().await.unwrap().into_iter().map(|c| c.config.hosts).collect();
+    hosts.sort();
+    assert_eq!(hosts, vec![vec!["http://a:9200"], vec!["http://b:9200"]]);
+
+    // Renaming keeps both rows and adds a third.
+    let summary = import_into(&db, "ambiguous", vec![connection("a", "B", "http://c:9200")], ConflictStrategy::Rename).await.unwrap();
+    assert_eq!(summary.renamed, 1);
+    assert_eq!(db.list_connections().await.unwrap().len(), 3);
+}
@@ -0,0 +1,152 @@
+//! The encrypted connection store across restarts.
+
+use std::path::PathBuf;
+use elastic_eye_lib::{
+    config::ElasticsearchConfig,
+    crypto::{self, KdfParams, MasterKey},
+    db::{ConnectionSource, Database, SavedConnection},
+    error::AppError,
+};
+
+fn database_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("elastic-eye-{}-{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn connection(id: &str, password: &str) -> SavedConnection {
+    SavedConnection {
+        id: id.to_string(),
+        name: id.to_string(),
+        config: ElasticsearchConfig {
+            hosts: vec!["http://localhost:9200".to_string()],
+            username: Some("elastic".to_string()),
+            password: Some(password.to_string()),
+            ..Default::default()
+        },
+        created_at: 1,
+        last_used_at: 1,
+        source: ConnectionSource::Database,
+    }
+}
+
+fn is_invalid_password(result: Result<(), AppError>) -> bool {
+    matches!(result, Err(AppError::ValidationError(message)) if message.key_name() == Some("vault.invalid_password"))
+}
+
+#[tokio::test]
+async fn locked_store_lists_connections_with_masked_secrets() {
+    let path = database_path("vault");
+
+    let db = Database::new(path.clone()).await.unwrap();
+    db.unlock("master".to_string()).await.unwrap();
+    db.save_connection(SavedConnection {
+        id: "prod".to_string(),
+        name: "Production".to_string(),
+        config: ElasticsearchConfig {
+            hosts: vec!["http://localhost:9200".to_string()],
+            username: Some("elastic".to_string()),
+            password: Some("changeme".to_string()),
+            ..Default::default()
+        },
+        created_at: 1,
+        last_used_at: 1,
+        source: ConnectionSource::Database,
+    })
+    .await
+    .unwrap();
+    drop(db);
+
+    // A restart leaves the store locked.
+    let db = Database::new(path.clone()).await.unwrap();
+    let listed = db.list_connections().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].config.username.as_deref(), Some("elastic"));
+    assert_eq!(listed[0].config.password.as_deref(), Some("********"));
+    assert!(matches!(db.get_connection("prod").await, Err(AppError::StateError(_))));
+
+    db.unlock("master".to_string()).await.unwrap();
+    let saved = db.get_connection("prod").await.unwrap();
+    assert_eq!(saved.config.password.as_deref(), Some("changeme"));
+
+    let kdf: String = rusqlite::Connection::open(&path)
+        .unwrap()
+        .query_row("SELECT kdf FROM vault", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(serde_json::from_str::<KdfParams>(&kdf).unwrap(), KdfParams::default());
+}
+
+#[test]
+fn secrets_only_decrypt_under_their_own_key() {
+    let kdf = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+    let salt = crypto::generate_salt();
+    let key = MasterKey::derive("master", &salt, &kdf).unwrap();
+
+    let encrypted = key.encrypt("changeme").unwrap();
+    assert!(crypto::is_encrypted(&encrypted));
+    assert_ne!(encrypted, key.encrypt("changeme").unwrap(), "every value gets a fresh nonce");
+    assert_eq!(key.decrypt(&encrypted).unwrap(), "changeme");
+
+    let key_name = |result: Result<String, AppError>| match result {
+        Err(AppError::StateError(message)) => message.key_name(),
+        other => panic!("expected a state error, got {:?}", other),
+    };
+    let other = MasterKey::derive("other", &salt, &kdf).unwrap();
+    assert_eq!(key_name(other.decrypt(&encrypted)), Some("vault.decrypt_failed"));
+    assert_eq!(key_name(key.decrypt("changeme")), Some("vault.not_encrypted"));
+    assert_eq!(key_name(key.decrypt("enc:v1:AAAA")), Some("vault.truncated_secret"));
+    assert!(MasterKey::derive("", &salt, &kdf).is_err());
+}
+
+#[tokio::test]
+async fn changing_the_master_password_rekeys_every_secret() {
+    let path = database_path("rekey");
+    let db = Database::new(path.clone()).await.unwrap();
+    db.unlock("old".to_string()).await.unwrap();
+    db.save_connection(connection("prod", "changeme")).await.unwrap();
+
+    assert!(is_invalid_password(db.change_master_password("wrong".to_string(), "new".to_string()).await));
+    db.change_master_password("old".to_string(), "new".to_string()).await.unwrap();
+    assert_eq!(db.get_connection("prod").await.unwrap().config.password.as_deref(), Some("changeme"));
+    drop(db);
+
+    let db = Database::new(path.clone()).await.unwrap();
+    assert!(is_invalid_password(db.unlock("old".to_string()).await));
+    db.unlock("new".to_string()).await.unwrap();
+    assert_eq!(db.get_connection("prod").await.unwrap().config.password.as_deref(), Some("changeme"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn plaintext_secrets_are_encrypted_on_unlock() {
+    let path = database_path("plaintext");
+    let db = Database::new(path.clone()).await.unwrap();
+
+    // Rows as written before secrets were encrypted: one from before the full config
+    // was stored, one with the config but a plaintext password.
+    let config = serde_json::to_string(&connection("current", "secret").config).unwrap();
+    let raw = rusqlite::Connection::open(&path).unwrap();
+    raw.execute(
+        "INSERT INTO connections (id, name, host, port, username, password, config, created_at, last_used_at)
+         VALUES ('legacy', 'legacy', 'localhost', 9200, 'elastic', 'changeme', NULL, 1, 2),
+                ('current', 'current', 'localhost', 9200, 'elastic', NULL, ?, 1, 1)",
+        [config],
+    )
+    .unwrap();
+
+    db.unlock("master".to_string()).await.unwrap();
+
+    let legacy: String = raw
+        .query_row("SELECT password FROM connections WHERE id = 'legacy'", [], |row| row.get(0))
+        .unwrap();
+    assert!(crypto::is_encrypted(&legacy));
+    let current: String = raw
+        .query_row("SELECT config FROM connections WHERE id = 'current'", [], |row| row.get(0))
+        .unwrap();
+    let current: ElasticsearchConfig = serde_json::from_str(&current).unwrap();
+    assert!(crypto::is_encrypted(current.password.as_deref().unwrap()));
+
+    assert_eq!(db.get_connection("legacy").await.unwrap().config.password.as_deref(), Some("changeme"));
+    assert_eq!(db.get_connection("current").await.unwrap().config.password.as_deref(), Some("secret"));
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,83 @@
+//! Server version and flavor detection from the root endpoint.
+
+use elastic_eye_lib::{
+    elasticsearch::version::{Distribution, Feature, ServerVersion},
+    error::AppError,
+};
+use serde_json::json;
+
+fn parse(version: serde_json::Value) -> Result<ServerVersion, AppError> {
+    ServerVersion::parse(&json!({ "name": "node-1", "version": version }))
+}
+
+#[test]
+fn elasticsearch_versions_and_flavors_are_parsed() {
+    let server = parse(json!({ "number": "7.17.9", "build_flavor": "oss" })).unwrap();
+    assert_eq!(server.distribution, Distribution::Elasticsearch);
+    assert_eq!((server.major, server.minor, server.patch), (7, 17, 9));
+    assert_eq!(server.build_flavor.as_deref(), Some("oss"));
+    assert_eq!(server.to_string(), "Elasticsearch 7.17.9");
+
+    let release_candidate = parse(json!({ "number": "8.0.0-rc1" })).unwrap();
+    assert_eq!((release_candidate.major, release_candidate.minor, release_candidate.patch), (8, 0, 0));
+    assert_eq!(release_candidate.build_flavor, None);
+
+    let snapshot = parse(json!({ "number": "7.17-SNAPSHOT" })).unwrap();
+    assert_eq!((snapshot.major, snapshot.minor, snapshot.patch), (7, 17, 0));
+}
+
+#[test]
+fn opensearch_is_recognized_even_in_compatibility_mode() {
+    let server = parse(json!({ "distribution": "opensearch", "number": "2.11.1" })).unwrap();
+    assert_eq!(server.distribution, Distribution::OpenSearch);
+    assert_eq!((server.major, server.minor, server.patch), (2, 11, 1));
+    assert_eq!(server.to_string(), "OpenSearch 2.11.1");
+
+    // OpenSearch 1.x answering as Elasticsearch 7.10.2 for older clients.
+    let compat = parse(json!({ "distribution": "opensearch", "number": "7.10.2" })).unwrap();
+    assert_eq!((compat.major, compat.minor, compat.patch), (1, 0, 0));
+}
+
+#[test]
+fn unusable_versions_are_rejected() {
+    let key = |result: Result<ServerVersion, AppError>| match result {
+        Err(AppError::ElasticsearchError(message)) | Err(AppError::UnsupportedError(message)) => message.key_name(),
+        other => panic!("expected an error, got {:?}", other),
+    };
+    assert_eq!(key(parse(json!({}))), Some("version.missing"));
+    assert_eq!(key(parse(json!({ "number": "latest" }))), Some("version.unrecognized"));
+    assert_eq!(key(parse(json!({ "number": "5.6.16" }))), Some("version.too_old"));
+    parse(json!({ "number": "6.8.23" })).unwrap();
+}
+
+#[test]
+fn features_follow_the_distribution_and_version() {
+    let es = |number| parse(json!({ "number": number })).unwrap();
+    let opensearch = |number| parse(json!({ "distribution": "opensearch", "number": number })).unwrap();
+
+    assert!(!es("7.9.3").supports(Feature::PointInTime));
+    assert!(es("7.10.0").supports(Feature::PointInTime));
+    assert!(!es("7.11.2").supports(Feature::ShardDocSort));
+    assert!(es("8.11.0").supports(Feature::ShardDocSort));
+    assert!(opensearch("2.4.0").supports(Feature::PointInTime));
+    assert!(!opensearch("2.3.0").supports(Feature::PointInTime));
+    assert!(!opensearch("2.11.0").supports(Feature::ShardDocSort));
+
+    let key = |result: Result<(), AppError>| match result {
+        Err(AppError::UnsupportedError(message)) => message.key_name(),
+        other => panic!("expected an unsupported error, got {:?}", other),
+    };
+    assert_eq!(key(es("7.9.3").require(Feature::PointInTime)), Some("version.feature_requires"));
+    assert_eq!(key(opensearch("2.11.0").require(Feature::ShardDocSort)), Some("version.feature_unavailable"));
+}
+
+#[test]
+fn typed_mappings_are_flattened_on_6x_only() {
+    let typed = json!({ "_doc": { "properties": { "status": { "type": "keyword" } } } });
+    let typeless = json!({ "properties": { "status": { "type": "keyword" } } });
+
+    let v6 = parse(json!({ "number": "6.8.23" })).unwrap();
+    assert_eq!(v6.normalize_mappings(typed.clone()), typeless);
+    let v7 = parse(json!({ "number": "7.17.9" })).unwrap();
+    assert_eq!(v7.normalize_mappings(typed.clone()), typed);
+}
@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 
 interface ClusterInfoProps {
   connectionId: string;
@@ -145,7 +146,7 @@ export function ClusterInfo({ connectionId }: ClusterInfoProps) {
         setHealth(healthData);
         setStats(statsData);
       } catch (err) {
-        setError(err instanceof Error ? err.message : String(err));
+        setError(formatError(err));
       } finally {
         setLoading(false);
       }
@@ -1,5 +1,6 @@
 import React, { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError, isAppError } from '@/services/error';
 
 interface ClusterInfo {
   name: string;
@@ -53,21 +54,11 @@ function ClusterOverview({ connectionId }: ClusterOverviewProps) {
       setClusterInfo(info);
     } catch (err) {
       console.error('Failed to fetch cluster info:', err);
-      let errorMessage = '获取集群信息失败';
-      if (err instanceof Error) {
-        errorMessage = err.message;
-      } else if (typeof err === 'object' && err !== null) {
-        // 处理可能的 Tauri 错误对象
-        const tauriError = err as { message?: string, ConnectionError?: string };
-        if (tauriError.ConnectionError === 'Not connected to Elasticsearch') {
-          errorMessage = '未连接到 Elasticsearch，请检查：\n1. Elasticsearch 服务是否正在运行\n2. 连接地址是否正确\n3. 用户名密码是否正确';
-        } else {
-          errorMessage = tauriError.message || tauriError.ConnectionError || JSON.stringify(err);
-        }
+      if (isAppError(err) && err.code === 'connection_error') {
+        setError('未连接到 Elasticsearch，请检查：\n1. Elasticsearch 服务是否正在运行\n2. 连接地址是否正确\n3. 用户名密码是否正确');
       } else {
-        errorMessage = String(err);
+        setError(formatError(err, '获取集群信息失败'));
       }
-      setError(errorMessage);
     } finally {
       setLoading(false);
     }
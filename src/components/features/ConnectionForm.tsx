@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 import { v4 as uuidv4 } from 'uuid';
 import { useAppStore } from '@/store';
 import {
@@ -145,13 +146,7 @@ function ConnectionForm({ onConnected, onCancel }: ConnectionFormProps) {
       addLog('触发 onConnected 回调');
     } catch (err) {
       console.error('连接失败:', err);
-      let errorMessage = err instanceof Error ? err.message : String(err);
-      if (typeof err === 'object' && err !== null) {
-        const tauriError = err as { message?: string, ConnectionError?: string };
-        if (tauriError.ConnectionError) {
-          errorMessage = `连接错误: ${tauriError.ConnectionError}`;
-        }
-      }
+      const errorMessage = formatError(err);
       setError(errorMessage);
       addLog(`连接失败: ${errorMessage}`);
     } finally {
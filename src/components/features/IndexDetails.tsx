@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError, isAppError } from '@/services/error';
 import { useParams, useNavigate } from 'react-router-dom';
 
 interface IndexDetails {
@@ -69,11 +70,11 @@ export function IndexDetails({ connectionId }: IndexDetailsProps) {
         setDetails(response);
       } catch (err) {
         console.error('Failed to fetch index details:', err);
-        if (err instanceof Error && err.message.includes('index_not_found')) {
+        if (isAppError(err) && err.code === 'not_found') {
           navigate('/indices');
           return;
         }
-        setError(err instanceof Error ? err.message : '获取索引详情失败');
+        setError(formatError(err, '获取索引详情失败'));
       } finally {
         setLoading(false);
       }
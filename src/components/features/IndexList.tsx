@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 import { useNavigate } from 'react-router-dom';
 
 interface IndexListProps {
@@ -73,7 +74,7 @@ function IndexList({ connectionId }: IndexListProps) {
       const indexList = await invoke<IndexInfo[]>('list_indices', { connectionId });
       setIndices(indexList);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
@@ -123,7 +124,7 @@ function IndexList({ connectionId }: IndexListProps) {
     } catch (err) {
       console.error('Failed to fetch index details:', err);
       // 显示错误提示
-      setError(err instanceof Error ? err.message : '获取索引详情失败');
+      setError(formatError(err, '获取索引详情失败'));
     } finally {
       setLoadingDetails(false);
     }
@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 
 interface IndicesProps {
   connectionId: string;
@@ -36,7 +37,7 @@ export function Indices({ connectionId }: IndicesProps) {
       const indexList = await invoke<IndexInfo[]>('list_indices', { connectionId });
       setIndices(indexList);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
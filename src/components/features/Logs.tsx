@@ -0,0 +1,88 @@
+import React, { useState, useEffect, useMemo } from 'react';
+import { LogViewer } from '@/components/common';
+import { formatError } from '@/services/error';
+import {
+  LogEntry,
+  LogLevel,
+  LOG_LEVELS,
+  atLevel,
+  formatLogEntry,
+  getLogDir,
+  getRecentLogs,
+  onLogEntry,
+} from '@/services/logs';
+
+const MAX_ENTRIES = 1000;
+
+function Logs() {
+  const [entries, setEntries] = useState<LogEntry[]>([]);
+  const [level, setLevel] = useState<LogLevel>('info');
+  const [logDir, setLogDir] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    const unlisten = onLogEntry(entry => {
+      setEntries(prev => [...prev, entry].slice(-MAX_ENTRIES));
+    });
+
+    getRecentLogs('trace')
+      .then(recent => {
+        if (!cancelled) setEntries(recent);
+      })
+      .catch(err => setError(formatError(err, '加载日志失败')));
+    getLogDir().then(setLogDir).catch(() => setLogDir(null));
+
+    return () => {
+      cancelled = true;
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const lines = useMemo(
+    () => entries.filter(entry => atLevel(entry, level)).map(formatLogEntry),
+    [entries, level]
+  );
+
+  return (
+    <div className="space-y-4 p-4">
+      <div className="flex items-center justify-between">
+        <div>
+          <h2 className="text-lg font-medium text-gray-900 dark:text-gray-100">日志</h2>
+          {logDir && (
+            <p className="text-sm text-gray-500">日志文件目录: {logDir}</p>
+          )}
+        </div>
+        <div className="flex items-center space-x-2">
+          <label htmlFor="log-level" className="text-sm text-gray-700 dark:text-gray-300">级别</label>
+          <select
+            id="log-level"
+            value={level}
+            onChange={e => setLevel(e.target.value as LogLevel)}
+            className="rounded-md border-gray-300 text-sm"
+          >
+            {LOG_LEVELS.map(item => (
+              <option key={item} value={item}>{item.toUpperCase()}</option>
+            ))}
+          </select>
+          <button
+            onClick={() => setEntries([])}
+            className="px-3 py-1 text-sm font-medium text-gray-700 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
+          >
+            清空
+          </button>
+        </div>
+      </div>
+
+      {error && <div className="text-sm text-red-600">{error}</div>}
+
+      {lines.length === 0 ? (
+        <div className="text-sm text-gray-500">暂无日志</div>
+      ) : (
+        <LogViewer logs={lines} maxHeight="calc(100vh - 12rem)" />
+      )}
+    </div>
+  );
+}
+
+export default Logs;
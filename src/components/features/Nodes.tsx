@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 import { Pagination } from '@/components/common';
 
 interface NodesProps {
@@ -41,7 +42,7 @@ export function Nodes({ connectionId }: NodesProps) {
       const nodeList = await invoke<NodeInfo[]>('list_nodes', { connectionId });
       setNodes(nodeList);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
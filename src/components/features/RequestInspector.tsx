@@ -0,0 +1,210 @@
+import React, { useState, useEffect, useCallback } from 'react';
+import { formatError } from '@/services/error';
+import {
+  AppSettings,
+  RequestRecord,
+  clearRequests,
+  getRequestCurl,
+  getSettings,
+  isFailed,
+  listRequests,
+  updateSettings,
+} from '@/services/inspector';
+
+interface RequestInspectorProps {
+  connectionId: string;
+}
+
+const METHODS = ['GET', 'HEAD', 'POST', 'PUT', 'DELETE'];
+
+function formatSize(bytes: number | null): string {
+  if (bytes === null) return '-';
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+}
+
+function RequestInspector({ connectionId }: RequestInspectorProps) {
+  const [records, setRecords] = useState<RequestRecord[]>([]);
+  const [selected, setSelected] = useState<RequestRecord | null>(null);
+  const [method, setMethod] = useState('');
+  const [path, setPath] = useState('');
+  const [failedOnly, setFailedOnly] = useState(false);
+  const [settings, setSettings] = useState<AppSettings | null>(null);
+  const [copied, setCopied] = useState<number | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      const result = await listRequests(connectionId, {
+        method: method || undefined,
+        path: path || undefined,
+        failed_only: failedOnly,
+      });
+      setRecords(result);
+      setError(null);
+    } catch (err) {
+      setError(formatError(err, '加载请求记录失败'));
+    }
+  }, [connectionId, method, path, failedOnly]);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  useEffect(() => {
+    getSettings().then(setSettings).catch(() => setSettings(null));
+  }, []);
+
+  const handleClear = async () => {
+    try {
+      await clearRequests(connectionId);
+      setSelected(null);
+      await refresh();
+    } catch (err) {
+      setError(formatError(err, '清空请求记录失败'));
+    }
+  };
+
+  const handleCopy = async (record: RequestRecord) => {
+    try {
+      const curl = await getRequestCurl(connectionId, record.id);
+      await navigator.clipboard.writeText(curl);
+      setCopied(record.id);
+      setTimeout(() => setCopied(null), 2000);
+    } catch (err) {
+      setError(formatError(err, '复制 curl 命令失败'));
+    }
+  };
+
+  const togglePersist = async () => {
+    if (!settings) return;
+    try {
+      setSettings(await updateSettings({ ...settings, persist_requests: !settings.persist_requests }));
+    } catch (err) {
+      setError(formatError(err, '保存设置失败'));
+    }
+  };
+
+  return (
+    <div className="space-y-4 p-4">
+      <div className="flex flex-wrap items-center justify-between gap-2">
+        <h2 className="text-lg font-medium text-gray-900 dark:text-gray-100">请求记录</h2>
+        <div className="flex flex-wrap items-center gap-2">
+          <select
+            value={method}
+            onChange={e => setMethod(e.target.value)}
+            className="rounded-md border-gray-300 text-sm"
+          >
+            <option value="">全部方法</option>
+            {METHODS.map(m => (
+              <option key={m} value={m}>{m}</option>
+            ))}
+          </select>
+          <input
+            type="text"
+            value={path}
+            onChange={e => setPath(e.target.value)}
+            placeholder="按路径过滤"
+            className="rounded-md border-gray-300 text-sm"
+          />
+          <label className="flex items-center space-x-1 text-sm text-gray-700 dark:text-gray-300">
+            <input type="checkbox" checked={failedOnly} onChange={e => setFailedOnly(e.target.checked)} />
+            <span>仅失败</span>
+          </label>
+          {settings && (
+            <label className="flex items-center space-x-1 text-sm text-gray-700 dark:text-gray-300">
+              <input type="checkbox" checked={settings.persist_requests} onChange={togglePersist} />
+              <span>保存到本地数据库</span>
+            </label>
+          )}
+          <button
+            onClick={refresh}
+            className="px-3 py-1 text-sm font-medium text-gray-700 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
+          >
+            刷新
+          </button>
+          <button
+            onClick={handleClear}
+            className="px-3 py-1 text-sm font-medium text-red-700 bg-white border border-red-300 rounded-md hover:bg-red-50"
+          >
+            清空
+          </button>
+        </div>
+      </div>
+
+      {error && <div className="text-sm text-red-600">{error}</div>}
+
+      <div className="overflow-x-auto">
+        <table className="min-w-full divide-y divide-gray-200 text-sm">
+          <thead className="bg-gray-50 dark:bg-gray-800">
+            <tr>
+              <th className="px-3 py-2 text-left font-medium text-gray-500">时间</th>
+              <th className="px-3 py-2 text-left font-medium text-gray-500">方法</th>
+              <th className="px-3 py-2 text-left font-medium text-gray-500">路径</th>
+              <th className="px-3 py-2 text-left font-medium text-gray-500">状态</th>
+              <th className="px-3 py-2 text-right font-medium text-gray-500">大小</th>
+              <th className="px-3 py-2 text-right font-medium text-gray-500">耗时</th>
+              <th className="px-3 py-2" />
+            </tr>
+          </thead>
+          <tbody className="divide-y divide-gray-200">
+            {records.length === 0 ? (
+              <tr>
+                <td colSpan={7} className="px-3 py-4 text-center text-gray-500">暂无请求记录</td>
+              </tr>
+            ) : (
+              records.map(record => (
+                <tr
+                  key={record.id}
+                  onClick={() => setSelected(record)}
+                  className={`cursor-pointer hover:bg-gray-50 ${selected?.id === record.id ? 'bg-indigo-50' : ''}`}
+                >
+                  <td className="px-3 py-2 whitespace-nowrap text-gray-500">
+                    {new Date(record.timestamp).toLocaleTimeString()}
+                  </td>
+                  <td className="px-3 py-2 font-mono">{record.method}</td>
+                  <td className="px-3 py-2 font-mono break-all">
+                    {record.path}{record.query ? `?${record.query}` : ''}
+                  </td>
+                  <td className={`px-3 py-2 ${isFailed(record) ? 'text-red-600' : 'text-green-600'}`}>
+                    {record.status ?? '失败'}
+                  </td>
+                  <td className="px-3 py-2 text-right">{formatSize(record.response_size)}</td>
+                  <td className="px-3 py-2 text-right">{record.duration_ms} ms</td>
+                  <td className="px-3 py-2 text-right">
+                    <button
+                      onClick={e => {
+                        e.stopPropagation();
+                        handleCopy(record);
+                      }}
+                      className="text-indigo-600 hover:text-indigo-900"
+                    >
+                      {copied === record.id ? '已复制' : '复制 curl'}
+                    </button>
+                  </td>
+                </tr>
+              ))
+            )}
+          </tbody>
+        </table>
+      </div>
+
+      {selected && (
+        <div className="space-y-2 rounded-md border border-gray-200 p-4">
+          <div className="font-mono text-sm break-all">
+            {selected.method} {selected.node}{selected.path}{selected.query ? `?${selected.query}` : ''}
+          </div>
+          {selected.error && <div className="text-sm text-red-600">{selected.error}</div>}
+          {selected.body && (
+            <pre className="bg-gray-50 dark:bg-gray-800 rounded-md p-3 text-xs overflow-auto max-h-64">
+              {JSON.stringify(JSON.parse(selected.body), null, 2)}
+            </pre>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
+
+export default RequestInspector;
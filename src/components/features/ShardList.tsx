@@ -1,5 +1,6 @@
 import React, { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 
 interface ShardInfo {
   index: string;
@@ -28,7 +29,7 @@ function ShardList({ connectionId }: ShardListProps) {
       const shardList = await invoke<ShardInfo[]>('get_shards_info', { connectionId });
       setShards(shardList);
     } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to fetch shards');
+      setError(formatError(err, 'Failed to fetch shards'));
     } finally {
       setLoading(false);
     }
@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 
 interface ShardsProps {
   connectionId: string;
@@ -34,7 +35,7 @@ function Shards({ connectionId }: ShardsProps) {
       const shardList = await invoke<ShardInfo[]>('list_shards', { connectionId });
       setShards(shardList);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
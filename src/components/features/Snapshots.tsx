@@ -1,5 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 
 interface SnapshotsProps {
   connectionId: string;
@@ -92,7 +93,7 @@ function Snapshots({ connectionId }: SnapshotsProps) {
       }
     } catch (err) {
       console.error('Failed to load repositories:', err);
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
@@ -113,7 +114,7 @@ function Snapshots({ connectionId }: SnapshotsProps) {
       const snaps = await invoke<Snapshot[]>('list_snapshots', { connectionId, repository });
       setSnapshots(snaps);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
@@ -180,7 +181,7 @@ function Snapshots({ connectionId }: SnapshotsProps) {
       alert(`仓库创建成功。\n\n注意：如果使用文件系统类型，请确保：\n1. 路径 ${newRepoLocation} 在 Elasticsearch 节点上存在并可写\n2. 该路径已在 elasticsearch.yml 中的 path.repo 配置中列为允许的仓库路径`);
     } catch (err) {
       console.error('Failed to create repository:', err);
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
@@ -200,7 +201,7 @@ function Snapshots({ connectionId }: SnapshotsProps) {
       setShowCreateSnapshot(false);
       loadSnapshots(selectedRepository);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
@@ -221,7 +222,7 @@ function Snapshots({ connectionId }: SnapshotsProps) {
       });
       loadSnapshots(selectedRepository);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
@@ -243,7 +244,7 @@ function Snapshots({ connectionId }: SnapshotsProps) {
       });
       alert('快照恢复已开始，请稍后检查集群状态');
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     } finally {
       setLoading(false);
     }
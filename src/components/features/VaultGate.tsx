@@ -0,0 +1,122 @@
+import React, { useState, useEffect } from 'react';
+import { formatError } from '@/services/error';
+import { getVaultStatus, unlockVault, VaultStatus } from '@/services/vault';
+import {
+  LoadingButton,
+  ErrorMessage,
+  Input,
+  Label,
+  Card,
+  CardContent,
+  CardHeader,
+  CardTitle,
+  CardDescription,
+} from '@/components/ui';
+
+interface VaultGateProps {
+  children: React.ReactNode;
+}
+
+/** 启动时解锁连接保险库，首次使用时设置主密码 */
+function VaultGate({ children }: VaultGateProps) {
+  const [status, setStatus] = useState<VaultStatus | null>(null);
+  const [password, setPassword] = useState('');
+  const [confirm, setConfirm] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    getVaultStatus()
+      .then(setStatus)
+      .catch(err => setError(formatError(err, '无法读取保险库状态')));
+  }, []);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!status?.initialized && password !== confirm) {
+      setError('两次输入的主密码不一致');
+      return;
+    }
+
+    setLoading(true);
+    setError(null);
+    try {
+      await unlockVault(password);
+      setStatus({ initialized: true, unlocked: true });
+    } catch (err) {
+      setError(formatError(err, '解锁失败'));
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  if (status?.unlocked) {
+    return <>{children}</>;
+  }
+
+  return (
+    <div className="flex items-center justify-center min-h-screen p-4 bg-gray-50 dark:bg-gray-900">
+      <div className="w-full max-w-md">
+        <Card>
+          <CardHeader>
+            <CardTitle>{status && !status.initialized ? '设置主密码' : '解锁连接'}</CardTitle>
+            <CardDescription>
+              {status && !status.initialized
+                ? '已保存连接的密码和密钥将使用主密码加密。主密码无法找回，请妥善保管。'
+                : '输入主密码以解密已保存连接的密码和密钥。'}
+            </CardDescription>
+          </CardHeader>
+          <CardContent>
+            <form onSubmit={handleSubmit} className="space-y-6">
+              <div className="space-y-4">
+                <div>
+                  <Label htmlFor="master-password">主密码</Label>
+                  <Input
+                    id="master-password"
+                    type="password"
+                    value={password}
+                    onChange={(e) => setPassword(e.target.value)}
+                    autoFocus
+                  />
+                </div>
+
+                {status && !status.initialized && (
+                  <div>
+                    <Label htmlFor="master-password-confirm">确认主密码</Label>
+                    <Input
+                      id="master-password-confirm"
+                      type="password"
+                      value={confirm}
+                      onChange={(e) => setConfirm(e.target.value)}
+                    />
+                  </div>
+                )}
+              </div>
+
+              {error && (
+                <ErrorMessage
+                  title="保险库错误"
+                  message={error}
+                  onRetry={() => setError(null)}
+                />
+              )}
+
+              <div className="flex justify-end">
+                <LoadingButton
+                  type="submit"
+                  loading={loading}
+                  disabled={!status || !password}
+                  className="inline-flex justify-center px-4 py-2 text-sm font-medium text-white bg-indigo-600 border border-transparent rounded-md shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                >
+                  {status && !status.initialized ? '设置并解锁' : '解锁'}
+                </LoadingButton>
+              </div>
+            </form>
+          </CardContent>
+        </Card>
+      </div>
+    </div>
+  );
+}
+
+export default VaultGate;
@@ -4,7 +4,10 @@ export { default as Indices } from './Indices';
 export { default as Nodes } from './Nodes';
 export { default as Shards } from './Shards';
 export { default as Snapshots } from './Snapshots';
+export { default as Logs } from './Logs';
+export { default as RequestInspector } from './RequestInspector';
 export { default as ConnectionForm } from './ConnectionForm';
 export { default as ConnectionList } from './ConnectionList';
 export { default as IndexList } from './IndexList';
-export { default as ShardList } from './ShardList'; 
\ No newline at end of file
+export { default as ShardList } from './ShardList';
+export { default as VaultGate } from './VaultGate'; 
\ No newline at end of file
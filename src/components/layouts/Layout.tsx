@@ -34,6 +34,8 @@ function Layout({ children }: LayoutProps) {
     { name: '索引', href: '/dashboard/indices' },
     { name: '搜索', href: '/dashboard/search' },
     { name: '快照', href: '/dashboard/snapshots' },
+    { name: '日志', href: '/dashboard/logs' },
+    { name: '请求', href: '/dashboard/requests' },
   ], []);
 
   // 使用 useCallback 缓存事件处理函数
@@ -103,6 +105,8 @@ function Layout({ children }: LayoutProps) {
   useHotkeys('cmd+3', () => navigate('/dashboard/indices'), [navigate]);
   useHotkeys('cmd+4', () => navigate('/dashboard/search'), [navigate]);
   useHotkeys('cmd+5', () => navigate('/dashboard/snapshots'), [navigate]);
+  useHotkeys('cmd+6', () => navigate('/dashboard/logs'), [navigate]);
+  useHotkeys('cmd+7', () => navigate('/dashboard/requests'), [navigate]);
   useHotkeys('cmd+shift+d', toggleDarkMode, [toggleDarkMode]);
   useHotkeys('cmd+shift+n', () => setShowConnectionForm(true), []);
   useHotkeys('?', () => setShowHotkeysHelp(true), []);
@@ -1,12 +1,14 @@
 import React, { useState, useEffect, useMemo, useRef, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
+import { ExportProgress, ResultFormat, cancelExport, onExportProgress, startExport } from '@/services/export';
 import {
   DropdownMenu,
   DropdownMenuContent,
   DropdownMenuItem,
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu";
-import { SearchProps, SearchResult, SearchResponse, IndexInfo, Column, ColumnState, FieldMetadata, SearchCondition, SavedSearch } from '../types';
+import { SearchProps, SearchResult, SearchResponse, SearchPage, IndexInfo, Column, ColumnState, FieldMetadata, SearchCondition, SavedSearch } from '../types';
 import { MAX_CELL_HEIGHT, commonFieldMetadata } from '../constants';
 import {  buildQuery, getOperatorOptions } from '../utils';
 
@@ -20,8 +22,21 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
   const [error, setError] = useState<string | null>(null);
   const [searchStats, setSearchStats] = useState<{
     total: number;
+    relation: string;
     took: number;
   } | null>(null);
+  // 分页: 已加载的页面、当前页和下一页的游标
+  const [pages, setPages] = useState<SearchResult[][]>([]);
+  const [pageIndex, setPageIndex] = useState(0);
+  const [nextCursor, setNextCursor] = useState<string | null>(null);
+  const sessionRef = useRef<string | null>(null);
+  // 导出: 格式、目标文件和当前导出的进度
+  const [showExportPanel, setShowExportPanel] = useState(false);
+  const [exportFormat, setExportFormat] = useState<ResultFormat>('csv');
+  const [exportPath, setExportPath] = useState('');
+  const [exportVisibleOnly, setExportVisibleOnly] = useState(true);
+  const [exportProgress, setExportProgress] = useState<ExportProgress | null>(null);
+  const exportIdRef = useRef<string | null>(null);
   const [selectedRow, setSelectedRow] = useState<SearchResult | null>(null);
   const [showRowDetail, setShowRowDetail] = useState(false);
   const [visibleColumns, setVisibleColumns] = useState<Set<string>>(new Set(['_id']));
@@ -98,7 +113,8 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
           setLoading(true);
           setError(null);
           
-          const response = await invoke<SearchResponse>('search', {
+          closeSearchSession();
+          const page = await invoke<SearchPage>('open_search', {
             connectionId,
             index: index.trim(),
             query: JSON.stringify({
@@ -106,14 +122,21 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
             })
           });
 
-          setResults(response.hits.hits);
+          sessionRef.current = page.session_id;
+          setPages([page.response.hits.hits]);
+          setPageIndex(0);
+          setNextCursor(page.next_cursor);
+          setResults(page.response.hits.hits);
           setSearchStats({
-            total: response.hits.total.value,
-            took: response.took
+            total: page.response.hits.total.value,
+            relation: page.response.hits.total.relation,
+            took: page.response.took
           });
         } catch (err) {
-          setError(err instanceof Error ? err.message : String(err));
+          setError(formatError(err));
           setResults([]);
+          setPages([]);
+          setNextCursor(null);
           setSearchStats(null);
         } finally {
           setLoading(false);
@@ -169,7 +192,7 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
         setIndex(indexList[0].name);
       }
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   };
 
@@ -224,7 +247,7 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
         setColumnOrder(['_id', ...fields.map(f => f.name)]);
       }
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   };
 
@@ -305,28 +328,120 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
       const searchQuery = buildQuery(searchConditions, availableFields);
       console.log('构建的查询:', searchQuery);
       
-      const response = await invoke<SearchResponse>('search', {
+      closeSearchSession();
+      const page = await invoke<SearchPage>('open_search', {
         connectionId,
         index: index.trim(),
         query: searchQuery
       });
-      console.log('搜索结果:', response);
+      console.log('搜索结果:', page);
 
-      setResults(response.hits.hits);
+      sessionRef.current = page.session_id;
+      setPages([page.response.hits.hits]);
+      setPageIndex(0);
+      setNextCursor(page.next_cursor);
+      setResults(page.response.hits.hits);
       setSearchStats({
-        total: response.hits.total.value,
-        took: response.took
+        total: page.response.hits.total.value,
+        relation: page.response.hits.total.relation,
+        took: page.response.took
       });
     } catch (err) {
       console.error('搜索错误:', err);
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
       setResults([]);
+      setPages([]);
+      setNextCursor(null);
       setSearchStats(null);
     } finally {
       setLoading(false);
     }
   };
 
+  const closeSearchSession = useCallback(() => {
+    if (sessionRef.current) {
+      invoke('close_search', { sessionId: sessionRef.current }).catch(() => {});
+      sessionRef.current = null;
+    }
+  }, []);
+
+  // 离开页面时释放 point in time
+  useEffect(() => closeSearchSession, [closeSearchSession]);
+
+  useEffect(() => {
+    const unlisten = onExportProgress(progress => {
+      if (progress.export_id === exportIdRef.current) {
+        setExportProgress(progress);
+      }
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const handleExport = async () => {
+    if (!exportPath.trim()) {
+      setError('请输入导出文件的路径');
+      return;
+    }
+    try {
+      setError(null);
+      const columns = exportVisibleOnly
+        ? columnOrder.filter(key => visibleColumns.has(key))
+        : undefined;
+      const exportId = await startExport(
+        connectionId,
+        index.trim(),
+        buildQuery(searchConditions, availableFields),
+        { format: exportFormat, path: exportPath.trim(), columns },
+      );
+      exportIdRef.current = exportId;
+      setExportProgress({
+        export_id: exportId,
+        state: 'running',
+        written: 0,
+        total: null,
+        path: exportPath.trim(),
+        error: null,
+        dropped_columns: [],
+      });
+    } catch (err) {
+      setError(formatError(err));
+    }
+  };
+
+  const exportRunning = exportProgress?.state === 'running';
+
+  const showPage = (target: number) => {
+    setPageIndex(target);
+    setResults(pages[target]);
+  };
+
+  const handleNextPage = async () => {
+    if (pageIndex + 1 < pages.length) {
+      showPage(pageIndex + 1);
+      return;
+    }
+    if (!nextCursor) return;
+
+    try {
+      setLoading(true);
+      setError(null);
+      const page = await invoke<SearchPage>('search_page', { connectionId, cursor: nextCursor });
+      setPages([...pages, page.response.hits.hits]);
+      setPageIndex(pageIndex + 1);
+      setNextCursor(page.next_cursor);
+      setResults(page.response.hits.hits);
+      setSearchStats(stats => stats && { ...stats, took: page.response.took });
+    } catch (err) {
+      setError(formatError(err));
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const hasNextPage = pageIndex + 1 < pages.length || nextCursor !== null;
+
   const addSearchCondition = () => {
     setSearchConditions([...searchConditions, { field: '', operator: '=', value: '' }]);
   };
@@ -744,7 +859,7 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
       // 刷新连接列表
       loadIndices();
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   };
 
@@ -1135,9 +1250,32 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
                       搜索结果
                     </h3>
                     <p className="mt-1 text-sm text-gray-500">
-                      找到 {searchStats.total} 条结果，耗时 {searchStats.took}ms
+                      找到 {searchStats.relation === 'gte' ? '至少 ' : ''}{searchStats.total} 条结果，耗时 {searchStats.took}ms
                     </p>
                   </div>
+                  <div className="flex items-center space-x-2">
+                    <button
+                      onClick={() => showPage(pageIndex - 1)}
+                      disabled={loading || pageIndex === 0}
+                      className="px-3 py-1 text-sm border border-gray-300 rounded-md disabled:opacity-50"
+                    >
+                      上一页
+                    </button>
+                    <span className="text-sm text-gray-500">第 {pageIndex + 1} 页</span>
+                    <button
+                      onClick={handleNextPage}
+                      disabled={loading || !hasNextPage}
+                      className="px-3 py-1 text-sm border border-gray-300 rounded-md disabled:opacity-50"
+                    >
+                      下一页
+                    </button>
+                    <button
+                      onClick={() => setShowExportPanel(!showExportPanel)}
+                      className="px-3 py-1 text-sm border border-gray-300 rounded-md"
+                    >
+                      导出全部
+                    </button>
+                  </div>
                   {selectedRows.size > 0 && (
                     <div className="flex space-x-3">
                       <button
@@ -1161,6 +1299,75 @@ const Search: React.FC<SearchProps> = ({ connectionId }) => {
                     </div>
                   )}
                 </div>
+
+                {/* 导出全部结果 */}
+                {(showExportPanel || exportProgress) && (
+                  <div className="mt-4 p-4 bg-gray-50 rounded-lg space-y-3">
+                    {showExportPanel && (
+                      <div className="flex flex-wrap items-center gap-3">
+                        <select
+                          value={exportFormat}
+                          onChange={(e) => setExportFormat(e.target.value as ResultFormat)}
+                          className="px-3 py-1 text-sm border border-gray-300 rounded-md"
+                        >
+                          <option value="csv">CSV</option>
+                          <option value="ndjson">NDJSON</option>
+                          <option value="json">JSON</option>
+                        </select>
+                        <input
+                          type="text"
+                          value={exportPath}
+                          onChange={(e) => setExportPath(e.target.value)}
+                          placeholder={`/path/to/${index || 'result'}.${exportFormat}`}
+                          className="flex-1 min-w-[240px] px-3 py-1 text-sm border border-gray-300 rounded-md"
+                        />
+                        <label className="inline-flex items-center text-sm text-gray-600">
+                          <input
+                            type="checkbox"
+                            className="form-checkbox h-4 w-4 text-indigo-600 rounded border-gray-300 mr-2"
+                            checked={exportVisibleOnly}
+                            onChange={(e) => setExportVisibleOnly(e.target.checked)}
+                          />
+                          仅导出显示的列
+                        </label>
+                        <button
+                          onClick={handleExport}
+                          disabled={exportRunning}
+                          className="px-4 py-1 rounded-md text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 disabled:opacity-50"
+                        >
+                          开始导出
+                        </button>
+                      </div>
+                    )}
+                    {exportProgress && (
+                      <div className="flex items-center justify-between text-sm">
+                        <span className={exportProgress.state === 'failed' ? 'text-red-600' : 'text-gray-600'}>
+                          {exportProgress.state === 'running' && `正在导出: 已写入 ${exportProgress.written}${exportProgress.total !== null ? ` / ${exportProgress.total}` : ''} 条`}
+                          {exportProgress.state === 'completed' && `已导出 ${exportProgress.written} 条到 ${exportProgress.path}`}
+                          {exportProgress.state === 'completed' && exportProgress.dropped_columns.length > 0 &&
+                            `，以下字段未出现在首批结果中而未导出: ${exportProgress.dropped_columns.join(', ')}`}
+                          {exportProgress.state === 'cancelled' && '导出已取消'}
+                          {exportProgress.state === 'failed' && `导出失败: ${formatError(exportProgress.error)}`}
+                        </span>
+                        {exportRunning ? (
+                          <button
+                            onClick={() => cancelExport(exportProgress.export_id).catch(() => {})}
+                            className="px-3 py-1 text-sm border border-gray-300 rounded-md"
+                          >
+                            取消
+                          </button>
+                        ) : (
+                          <button
+                            onClick={() => setExportProgress(null)}
+                            className="px-3 py-1 text-sm text-gray-500"
+                          >
+                            关闭
+                          </button>
+                        )}
+                      </div>
+                    )}
+                  </div>
+                )}
               </div>
 
               {results.length > 0 ? (
@@ -1,5 +1,6 @@
 import { useState, useEffect, useMemo, useCallback, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { formatError } from '@/services/error';
 import { SearchResult, SearchResponse, IndexInfo, SearchCondition, SavedSearch, FieldMetadata, Column, ColumnState } from '../types';
 import { commonFieldMetadata } from '../constants';
 import { buildQuery } from '../utils';
@@ -76,7 +77,7 @@ export function useSearch(connectionId: string) {
         setIndex(indexList[0].name);
       }
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   };
 
@@ -132,7 +133,7 @@ export function useSearch(connectionId: string) {
         setColumnOrder(['_id', ...fields.map(f => f.name)]);
       }
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   };
 
@@ -161,7 +162,7 @@ export function useSearch(connectionId: string) {
         took: response.took
       });
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
       setResults([]);
       setSearchStats(null);
     } finally {
@@ -210,7 +211,7 @@ export function useSearch(connectionId: string) {
       // 刷新连接列表
       loadIndices();
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   };
 
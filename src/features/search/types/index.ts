@@ -23,6 +23,13 @@ export interface SearchResponse {
   };
 }
 
+/** 基于 point in time 的分页结果 */
+export interface SearchPage {
+  session_id: string;
+  next_cursor: string | null;
+  response: SearchResponse;
+}
+
 export interface IndexInfo {
   name: string;
   docs_count: number;
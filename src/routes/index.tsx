@@ -17,6 +17,8 @@ const Search = React.lazy(() =>
   })
 );
 const Snapshots = React.lazy(() => import('../components/features/Snapshots'));
+const Logs = React.lazy(() => import('../components/features/Logs'));
+const RequestInspector = React.lazy(() => import('../components/features/RequestInspector'));
 
 // 包装路由组件，添加错误边界和加载状态
 const withErrorBoundary = (Component: React.ComponentType<any>) => (props: { connectionId: string }) => (
@@ -58,6 +60,14 @@ export const AppRoutes: React.FC<AppRoutesProps> = ({ connectionId }) => {
         path="/snapshots"
         element={withErrorBoundary(Snapshots)({ connectionId })}
       />
+      <Route
+        path="/logs"
+        element={withErrorBoundary(Logs)({ connectionId })}
+      />
+      <Route
+        path="/requests"
+        element={withErrorBoundary(RequestInspector)({ connectionId })}
+      />
       <Route path="*" element={<NotFound />} />
     </Routes>
   );
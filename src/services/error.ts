@@ -64,4 +64,39 @@ class ErrorService {
   }
 }
 
-export const errorService = new ErrorService(); 
\ No newline at end of file
+export const errorService = new ErrorService(); 
+
+/** Error returned by every Tauri command. */
+export interface AppError {
+  code: string;
+  /** Rendered in the locale chosen with the `update_settings` command. */
+  message: string;
+  /** Catalog key and params, when the message comes from the backend catalog. */
+  key: string | null;
+  params: Record<string, string> | null;
+  status: number | null;
+  details: {
+    status: number;
+    error: ErrorCause | null;
+    root_cause: ErrorCause[];
+    request: { method: string; path: string };
+    body: string | null;
+  } | null;
+}
+
+export interface ErrorCause {
+  type: string;
+  reason: string | null;
+  caused_by: ErrorCause | null;
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+export function formatError(err: unknown, fallback?: string): string {
+  if (isAppError(err) || err instanceof Error) {
+    return err.message;
+  }
+  return fallback ?? String(err);
+}
@@ -0,0 +1,45 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+export type ResultFormat = 'ndjson' | 'json' | 'csv';
+
+export type ExportState = 'running' | 'completed' | 'failed' | 'cancelled';
+
+export interface ExportTarget {
+  format: ResultFormat;
+  path: string;
+  /** 点分路径的字段及 `_index`、`_id`、`_score`，为空时导出整个文档 */
+  columns?: string[];
+}
+
+export interface ExportProgress {
+  export_id: string;
+  state: ExportState;
+  written: number;
+  /** 集群给出精确命中数时才有 */
+  total: number | null;
+  path: string;
+  error: unknown | null;
+  /** 未选择列的 CSV 导出中，首批结果之后才出现而未写入的字段 */
+  dropped_columns: string[];
+}
+
+export const EXPORT_EVENT = 'export-progress';
+
+/** 在后台导出查询的全部结果，返回导出 id */
+export function startExport(
+  connectionId: string,
+  index: string,
+  query: string,
+  target: ExportTarget,
+): Promise<string> {
+  return invoke<string>('start_export', { connectionId, index, query, target });
+}
+
+export function cancelExport(exportId: string): Promise<boolean> {
+  return invoke<boolean>('cancel_export', { exportId });
+}
+
+export function onExportProgress(handler: (progress: ExportProgress) => void): Promise<UnlistenFn> {
+  return listen<ExportProgress>(EXPORT_EVENT, event => handler(event.payload));
+}
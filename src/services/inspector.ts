@@ -0,0 +1,53 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface RequestRecord {
+  id: number;
+  connection_id: string;
+  timestamp: number;
+  method: string;
+  node: string;
+  path: string;
+  query: string | null;
+  body: string | null;
+  status: number | null;
+  response_size: number | null;
+  duration_ms: number;
+  error: string | null;
+}
+
+export interface RequestFilter {
+  method?: string;
+  path?: string;
+  failed_only?: boolean;
+  limit?: number;
+}
+
+export interface AppSettings {
+  locale: 'en' | 'zh-CN';
+  persist_requests: boolean;
+}
+
+/** 最新的请求在前 */
+export function listRequests(connectionId: string, filter?: RequestFilter): Promise<RequestRecord[]> {
+  return invoke<RequestRecord[]>('list_requests', { connectionId, filter });
+}
+
+export function clearRequests(connectionId: string): Promise<void> {
+  return invoke<void>('clear_requests', { connectionId });
+}
+
+export function getRequestCurl(connectionId: string, id: number): Promise<string> {
+  return invoke<string>('get_request_curl', { connectionId, id });
+}
+
+export function getSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>('get_settings');
+}
+
+export function updateSettings(settings: AppSettings): Promise<AppSettings> {
+  return invoke<AppSettings>('update_settings', { settings });
+}
+
+export function isFailed(record: RequestRecord): boolean {
+  return record.status === null || record.status < 200 || record.status >= 300;
+}
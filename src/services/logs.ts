@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export const LOG_LEVELS: LogLevel[] = ['error', 'warn', 'info', 'debug', 'trace'];
+
+export interface LogEntry {
+  timestamp: number;
+  level: LogLevel;
+  target: string;
+  message: string;
+  fields: Record<string, string>;
+}
+
+export const LOG_EVENT = 'log-entry';
+
+/** 后端缓冲的日志，`level` 及更严重的级别 */
+export function getRecentLogs(level?: LogLevel): Promise<LogEntry[]> {
+  return invoke<LogEntry[]>('get_recent_logs', { level });
+}
+
+export function getLogDir(): Promise<string | null> {
+  return invoke<string | null>('get_log_dir');
+}
+
+export function onLogEntry(handler: (entry: LogEntry) => void): Promise<UnlistenFn> {
+  return listen<LogEntry>(LOG_EVENT, event => handler(event.payload));
+}
+
+/** 是否达到 `level` 指定的严重程度 */
+export function atLevel(entry: LogEntry, level: LogLevel): boolean {
+  return LOG_LEVELS.indexOf(entry.level) <= LOG_LEVELS.indexOf(level);
+}
+
+export function formatLogEntry(entry: LogEntry): string {
+  const time = new Date(entry.timestamp).toLocaleTimeString();
+  const fields = Object.entries(entry.fields)
+    .map(([name, value]) => `${name}=${value}`)
+    .join(' ');
+  return `${time} ${entry.level.toUpperCase().padEnd(5)} ${entry.target}: ${entry.message}${fields ? ` ${fields}` : ''}`;
+}
@@ -0,0 +1,20 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface VaultStatus {
+  /** 是否已设置主密码 */
+  initialized: boolean;
+  unlocked: boolean;
+}
+
+export function getVaultStatus(): Promise<VaultStatus> {
+  return invoke<VaultStatus>('get_vault_status');
+}
+
+/** 首次调用时以该密码创建保险库，并加密已保存的明文密钥 */
+export function unlockVault(masterPassword: string): Promise<void> {
+  return invoke('unlock_vault', { masterPassword });
+}
+
+export function changeMasterPassword(currentPassword: string, newPassword: string): Promise<void> {
+  return invoke('change_master_password', { currentPassword, newPassword });
+}